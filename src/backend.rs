@@ -0,0 +1,225 @@
+//! Where a store is kept. A backend loads the whole `Memory` and saves it
+//! back; which one is used depends on the location of the store:
+//!
+//! | location                         | backend                        |
+//! |----------------------------------|--------------------------------|
+//! | `memory:`                        | kept in memory only, for tests |
+//! | `json:<path>`                    | a JSON file                    |
+//! | `sqlite:<path>`, `<path>.db`     | an SQLite database             |
+//! | `log:<path>`, `<path>.jsonl`     | a log of changes, see `journal`|
+//! | `<path>`                         | a JSON file                    |
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::prelude::*;
+
+use crate::item::Item;
+use crate::journal::Journal;
+use crate::memory::{self, Memory, MemoryError, Serializable};
+use crate::sqlite::Sqlite;
+use crate::watch::Watch;
+
+type Result<T> = std::result::Result<T, MemoryError>;
+
+pub trait Backend<K, V>
+where
+    K: Serializable,
+    V: Serializable,
+{
+    fn load(&mut self) -> Result<Memory<K, V>>;
+    fn save(&mut self, memory: &Memory<K, V>) -> Result<()>;
+
+    /// The file the store is kept in, if any.
+    fn path(&self) -> Option<&str> {
+        None
+    }
+
+    /// Tells when the store was changed on disk, by a script or another
+    /// go22dos.
+    fn watch(&self) -> Option<Watch> {
+        self.path().and_then(|path| Watch::new(path).ok())
+    }
+
+    /// Whether saving is cheap enough to be done after every change, rather
+    /// than once go22dos quits.
+    fn saves_each_change(&self) -> bool {
+        false
+    }
+
+    /// Takes the advisory lock of the store for as long as the backend
+    /// lives, false if another go22dos holds it.
+    fn lock(&mut self) -> Result<bool> {
+        Ok(true)
+    }
+}
+
+/// Takes the advisory lock of the store at `path` into `lock`, false if it is
+/// held elsewhere. The lock is kept on `<path>.lock` next to the store, so
+/// that it holds for stores yet to be written and for ones replaced as a
+/// whole.
+pub fn try_lock(path: &str, lock: &mut Option<File>) -> Result<bool> {
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(format!("{path}.lock"))?;
+    match file.try_lock() {
+        Ok(()) => {
+            *lock = Some(file);
+            Ok(true)
+        }
+        Err(TryLockError::WouldBlock) => Ok(false),
+        Err(TryLockError::Error(e)) => Err(e.into()),
+    }
+}
+
+/// The whole store as a single JSON file, rewritten on every save.
+pub struct Json {
+    path: String,
+    lock: Option<File>,
+}
+
+impl Json {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            lock: None,
+        }
+    }
+}
+
+impl<K, V> Backend<K, V> for Json
+where
+    K: Serializable,
+    V: Serializable,
+{
+    fn load(&mut self) -> Result<Memory<K, V>> {
+        memory::deserialize(&self.path).ok_or(MemoryError::Unknown)
+    }
+
+    fn save(&mut self, memory: &Memory<K, V>) -> Result<()> {
+        let mut f = File::create(&self.path).map_err(MemoryError::IoError)?;
+        let data = memory.serialize().ok_or(MemoryError::Unknown)?;
+        f.write_all(data.as_bytes()).map_err(MemoryError::IoError)
+    }
+
+    fn path(&self) -> Option<&str> {
+        Some(&self.path)
+    }
+
+    fn lock(&mut self) -> Result<bool> {
+        try_lock(&self.path, &mut self.lock)
+    }
+}
+
+/// A store which is gone once the program ends.
+pub struct InMemory<K, V>
+where
+    K: Serializable,
+    V: Serializable,
+{
+    memory: Option<Memory<K, V>>,
+}
+
+impl<K, V> InMemory<K, V>
+where
+    K: Serializable,
+    V: Serializable,
+{
+    pub fn new() -> Self {
+        Self { memory: None }
+    }
+}
+
+impl<K, V> Backend<K, V> for InMemory<K, V>
+where
+    K: Serializable,
+    V: Serializable,
+{
+    fn load(&mut self) -> Result<Memory<K, V>> {
+        Ok(self.memory.clone().unwrap_or_else(Memory::new))
+    }
+
+    fn save(&mut self, memory: &Memory<K, V>) -> Result<()> {
+        self.memory = Some(memory.clone());
+        Ok(())
+    }
+}
+
+/// Extensions of SQLite databases given as a bare path.
+const SQLITE_EXTENSIONS: [&str; 3] = ["db", "sqlite", "sqlite3"];
+
+/// The backend of a store by its location.
+pub fn open(location: &str) -> Result<Box<dyn Backend<String, Item>>> {
+    let extension = std::path::Path::new(location)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_lowercase);
+    match (location.split_once(':'), extension.as_deref()) {
+        (Some(("memory", _)), _) => Ok(Box::new(InMemory::new())),
+        (Some(("json", path)), _) => Ok(Box::new(Json::new(path))),
+        (Some(("sqlite", path)), _) => Ok(Box::new(Sqlite::open(path)?)),
+        (Some(("log", path)), _) => Ok(Box::new(Journal::new(path))),
+        (_, Some(extension)) if SQLITE_EXTENSIONS.contains(&extension) => {
+            Ok(Box::new(Sqlite::open(location)?))
+        }
+        (_, Some("jsonl")) => Ok(Box::new(Journal::new(location))),
+        _ => Ok(Box::new(Json::new(location))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::memory::MemoryManagement;
+    use chrono::Utc;
+    use std::sync::{Arc, Mutex};
+
+    fn saved(backend: &mut dyn Backend<String, Item>) -> Memory<String, Item> {
+        let mut a2do = Arc::new(Mutex::new(backend.load().unwrap()));
+        a2do.add_topic("t").unwrap();
+        a2do.add_item(0, Item::new("a", Utc::now())).unwrap();
+        a2do.save(backend).unwrap();
+        backend.load().unwrap()
+    }
+
+    #[test]
+    fn backends() {
+        let mut backend = open("memory:").unwrap();
+        let memory = saved(backend.as_mut());
+        assert_eq!(memory.idxs, vec!["t"]);
+        assert_eq!(memory.data["t"][0].text(), "a");
+
+        let path = std::env::temp_dir().join(format!("go22dos-{}.json", std::process::id()));
+        let path = path.to_string_lossy();
+        std::fs::write(path.as_ref(), "{}").unwrap();
+        let mut backend = open(&format!("json:{path}")).unwrap();
+        let memory = saved(backend.as_mut());
+        let _ = std::fs::remove_file(path.as_ref());
+        assert_eq!(memory.idxs, vec!["t"]);
+        assert_eq!(memory.data["t"][0].text(), "a");
+    }
+
+    #[test]
+    fn locks() {
+        let path = std::env::temp_dir().join(format!("go22dos-locked-{}.json", std::process::id()));
+        let path = path.to_string_lossy();
+        // not written yet
+        let mut first = open(&path).unwrap();
+        let mut second = open(&path).unwrap();
+        assert!(first.lock().unwrap());
+        assert!(!second.lock().unwrap());
+        drop(first);
+        assert!(second.lock().unwrap());
+
+        std::fs::write(path.as_ref(), "{}").unwrap();
+        let mut first = open(&path).unwrap();
+        assert!(!first.lock().unwrap());
+        drop(second);
+        assert!(first.lock().unwrap());
+        // replaced as a whole
+        std::fs::write(format!("{path}.tmp"), "{}").unwrap();
+        std::fs::rename(format!("{path}.tmp"), path.as_ref()).unwrap();
+        assert!(!open(&path).unwrap().lock().unwrap());
+        let _ = std::fs::remove_file(path.as_ref());
+        let _ = std::fs::remove_file(format!("{path}.lock"));
+    }
+}
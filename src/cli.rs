@@ -0,0 +1,168 @@
+//! Command line arguments:
+//!
+//! ```text
+//! go22dos [store]
+//! go22dos import <file> [--store <store>]
+//! go22dos export <file> [topic ...] [--split] [--store <store>]
+//! go22dos report --html <file> [--store <store>]
+//! go22dos scan [dir] [--topic <topic>] [--store <store>]
+//! go22dos migrate <store> [--store <store>]
+//! ```
+//!
+//! Without a store `_.go22dos` in the current directory is used.
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    /// the todos app itself
+    Tui,
+    Import {
+        file: String,
+    },
+    /// all topics unless some are named, to a file each when split
+    Export {
+        file: String,
+        topics: Vec<String>,
+        split: bool,
+    },
+    /// a report of all topics
+    Report {
+        html: String,
+    },
+    /// todo comments below a directory into a topic
+    Scan {
+        dir: String,
+        topic: Option<String>,
+    },
+    /// the store into another one, e.g. from json to sqlite
+    Migrate {
+        to: String,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Args {
+    pub store: Option<String>,
+    pub command: Command,
+}
+
+pub const USAGE: &str = "usage: go22dos [store]
+       go22dos import <file> [--store <store>]
+       go22dos export <file> [topic ...] [--split] [--store <store>]
+       go22dos report --html <file> [--store <store>]
+       go22dos scan [dir] [--topic <topic>] [--store <store>]
+       go22dos migrate <store> [--store <store>]";
+
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+    let mut store = None;
+    let mut split = false;
+    let mut html = None;
+    let mut topic = None;
+    let mut words = vec![];
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--store" | "-s" => store = Some(args.next().ok_or(USAGE)?),
+            "--split" => split = true,
+            "--html" => html = Some(args.next().ok_or(USAGE)?),
+            "--topic" => topic = Some(args.next().ok_or(USAGE)?),
+            "--help" | "-h" => return Err(USAGE.to_owned()),
+            _ => words.push(arg),
+        }
+    }
+
+    let mut words = words.into_iter();
+    let command = match words.next().as_deref() {
+        None => Command::Tui,
+        Some("import") => Command::Import {
+            file: words.next().ok_or(USAGE)?,
+        },
+        Some("export") => Command::Export {
+            file: words.next().ok_or(USAGE)?,
+            topics: words.by_ref().collect(),
+            split,
+        },
+        Some("report") => Command::Report {
+            html: html.take().ok_or(USAGE)?,
+        },
+        Some("scan") => Command::Scan {
+            dir: words.next().unwrap_or(".".to_owned()),
+            topic: topic.take(),
+        },
+        Some("migrate") => Command::Migrate {
+            to: words.next().ok_or(USAGE)?,
+        },
+        // the store as the only argument, as it has always been
+        Some(s) => {
+            store = Some(s.to_owned());
+            Command::Tui
+        }
+    };
+    let misplaced =
+        split && !matches!(command, Command::Export { .. }) || html.is_some() || topic.is_some();
+    match words.next() {
+        Some(_) => Err(USAGE.to_owned()),
+        None if misplaced => Err(USAGE.to_owned()),
+        None => Ok(Args { store, command }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(s: &str) -> Result<Args, String> {
+        parse(s.split_whitespace().map(str::to_owned))
+    }
+
+    #[test]
+    fn commands() {
+        assert_eq!(
+            args(""),
+            Ok(Args {
+                store: None,
+                command: Command::Tui
+            })
+        );
+        assert_eq!(
+            args("todos.json").unwrap().store.as_deref(),
+            Some("todos.json")
+        );
+        assert_eq!(
+            args("export out.md work home -s todos.json"),
+            Ok(Args {
+                store: Some("todos.json".to_owned()),
+                command: Command::Export {
+                    file: "out.md".to_owned(),
+                    topics: vec!["work".to_owned(), "home".to_owned()],
+                    split: false,
+                }
+            })
+        );
+        assert!(args("import").is_err());
+        assert!(args("import a.md --split").is_err());
+        assert_eq!(
+            args("report --html out.html").unwrap().command,
+            Command::Report {
+                html: "out.html".to_owned()
+            }
+        );
+        assert!(args("report").is_err());
+        assert!(args("export a.md --html b.html").is_err());
+        assert_eq!(
+            args("scan --topic todos").unwrap().command,
+            Command::Scan {
+                dir: ".".to_owned(),
+                topic: Some("todos".to_owned())
+            }
+        );
+        assert!(args("--topic todos").is_err());
+        assert_eq!(
+            args("migrate todos.db").unwrap().command,
+            Command::Migrate {
+                to: "todos.db".to_owned()
+            }
+        );
+        assert!(args("migrate").is_err());
+        assert!(args("a b").is_err());
+    }
+}
@@ -1,14 +1,34 @@
-pub const START_SCREEN: [&'static str; 10] = [
-    "            go22dos                   ",
-    "                                      ",
-    "type t     to go to todos             ",
-    "type j/k   to go down/up              ",
-    "type s     to select specific todos   ",
-    "type TAB   to tick off todo           ",
-    "type a     to add todo(s)             ",
-    "type d     to delete todo(s)          ",
-    "type ESC   to exit todos or text input",
-    "type q     to quit                    ",
+pub const DATA: &str = "_.go22dos";
+/// environment variable with the location of the store, see `backend`
+pub const STORE_VAR: &str = "GO22DOS_STORE";
+/// topic the todos of `go22dos scan` go to
+pub const SCAN_TOPIC: &str = "code";
+/// milliseconds between looks for changes to the store while waiting for a key
+pub const WATCH_INTERVAL: i32 = 250;
+
+pub const START_SCREEN: [&str; 22] = [
+    "            go22dos                     ",
+    "                                        ",
+    "type t     to go to todos               ",
+    "type j/k   to go down/up                ",
+    "type s     to select specific todos     ",
+    "type TAB   to tick off todo             ",
+    "type +/-   to raise/lower priority      ",
+    "type a     to add todo(s)               ",
+    "type >/<   to indent/outdent a todo     ",
+    "type z     to expand/collapse subtodos  ",
+    "type due:  to set when a todo is due    ",
+    "type rec:  to make a todo recur         ",
+    "type n     to write notes on a todo     ",
+    "type v/V   to edit a todo/all in $EDITOR",
+    "type d     to delete todo(s)            ",
+    "type h/o   to hide done/change order    ",
+    "type m     to remember views            ",
+    "type #     to see todos with a tag      ",
+    "type I/E   to import/export todos       ",
+    "type x/X   to archive/browse archived   ",
+    "type ESC   to exit todos or text input  ",
+    "type q     to quit                      ",
 ];
 
 const REGULAR_PAIR: i16 = 0;
@@ -20,9 +40,21 @@ const HIGHLIGHT_PAIR_SND: i16 = ncurses::COLOR_WHITE;
 pub const CHECKBOX_TODO_PAIR: i16 = 2;
 const CHECKBOX_TODO_PAIR_FST: i16 = ncurses::COLOR_BLACK;
 const CHECKBOX_TODO_PAIR_SND: i16 = ncurses::COLOR_RED;
+pub const OVERDUE_PAIR: i16 = 4;
+const OVERDUE_PAIR_FST: i16 = ncurses::COLOR_BLACK;
+const OVERDUE_PAIR_SND: i16 = ncurses::COLOR_MAGENTA;
 pub const CHECKBOX_DONE_PAIR: i16 = 3;
 const CHECKBOX_DONE_PAIR_FST: i16 = ncurses::COLOR_BLACK;
 const CHECKBOX_DONE_PAIR_SND: i16 = ncurses::COLOR_GREEN;
+pub const PRIORITY_HIGH_PAIR: i16 = 5;
+const PRIORITY_HIGH_PAIR_FST: i16 = ncurses::COLOR_BLACK;
+const PRIORITY_HIGH_PAIR_SND: i16 = ncurses::COLOR_YELLOW;
+pub const PRIORITY_MEDIUM_PAIR: i16 = 7;
+const PRIORITY_MEDIUM_PAIR_FST: i16 = ncurses::COLOR_BLACK;
+const PRIORITY_MEDIUM_PAIR_SND: i16 = ncurses::COLOR_BLUE;
+pub const TAG_PAIR: i16 = 8;
+const TAG_PAIR_FST: i16 = ncurses::COLOR_YELLOW;
+const TAG_PAIR_SND: i16 = ncurses::COLOR_BLACK;
 pub const OTHER_PAIR: i16 = 6;
 const OTHER_PAIR_FST: i16 = ncurses::COLOR_BLACK;
 const OTHER_PAIR_SND: i16 = ncurses::COLOR_CYAN;
@@ -35,26 +67,62 @@ pub fn init_pairs() {
         CHECKBOX_TODO_PAIR_FST,
         CHECKBOX_TODO_PAIR_SND,
     );
+    ncurses::init_pair(OVERDUE_PAIR, OVERDUE_PAIR_FST, OVERDUE_PAIR_SND);
     ncurses::init_pair(
         CHECKBOX_DONE_PAIR,
         CHECKBOX_DONE_PAIR_FST,
         CHECKBOX_DONE_PAIR_SND,
     );
+    ncurses::init_pair(
+        PRIORITY_HIGH_PAIR,
+        PRIORITY_HIGH_PAIR_FST,
+        PRIORITY_HIGH_PAIR_SND,
+    );
+    ncurses::init_pair(
+        PRIORITY_MEDIUM_PAIR,
+        PRIORITY_MEDIUM_PAIR_FST,
+        PRIORITY_MEDIUM_PAIR_SND,
+    );
+    ncurses::init_pair(TAG_PAIR, TAG_PAIR_FST, TAG_PAIR_SND);
     ncurses::init_pair(OTHER_PAIR, OTHER_PAIR_FST, OTHER_PAIR_SND);
 }
 
-//pub const ENTER: i32 = 13;
 pub const TAB: i32 = 9;
+pub const ENTER: i32 = 10;
 pub const ESC: i32 = 27;
+pub const TAGS: i32 = 35;
+pub const MORE: i32 = 43;
+pub const LESS: i32 = 45;
+pub const OUTDENT: i32 = 60;
+pub const INDENT: i32 = 62;
+pub const EXPORT: i32 = 69;
 pub const BG: i32 = 71;
+pub const IMPORT: i32 = 73;
+pub const BULK: i32 = 86;
+pub const ARCHIVES: i32 = 88;
 pub const APPEND: i32 = 97;
 pub const EDIT: i32 = 98;
 pub const DELETE: i32 = 100;
 pub const EXIT: i32 = 101;
 pub const SG: i32 = 103;
+pub const HIDE: i32 = 104;
 pub const DOWN: i32 = 106;
 pub const UP: i32 = 107;
+pub const REMEMBER: i32 = 109;
+pub const NOTES: i32 = 110;
+pub const SORT: i32 = 111;
 pub const QUIT: i32 = 113;
 pub const SELECT: i32 = 115;
 pub const GO_TO_TODOS: i32 = 116;
+pub const RESTORE: i32 = 117;
+pub const VISUAL: i32 = 118;
+pub const ARCHIVE: i32 = 120;
+pub const FOLD: i32 = 122;
 pub const REMOVE: i32 = 127;
+
+/// Keys which change the store, refused while it is read-only.
+pub const CHANGES: [i32; 14] = [
+    TAB, APPEND, EDIT, DELETE, NOTES, VISUAL, BULK, MORE, LESS, INDENT, OUTDENT, ARCHIVE, RESTORE,
+    IMPORT,
+];
+pub const READ_ONLY: &str = "read-only, open elsewhere";
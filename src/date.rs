@@ -0,0 +1,198 @@
+use chrono::{Datelike, Days, Months, NaiveDate, TimeDelta, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Parses a date as typed in by hand: `today`, `tomorrow`, `yesterday`,
+/// offsets such as `+3d` or `+2w`, weekdays (`mon`, `friday`) meaning their
+/// next occurrence, and `YYYY-MM-DD`.
+pub fn parse(s: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let s = s.trim().to_lowercase();
+    match s.as_str() {
+        "today" => return Some(today),
+        "tomorrow" => return today.succ_opt(),
+        "yesterday" => return today.pred_opt(),
+        _ => (),
+    }
+    if let Some(offset) = s.strip_prefix('+') {
+        return offset_days(offset)
+            .and_then(TimeDelta::try_days)
+            .and_then(|offset| today.checked_add_signed(offset));
+    }
+    if let Some(weekday) = weekday(&s) {
+        return next_weekday(today, weekday);
+    }
+    NaiveDate::parse_from_str(&s, "%Y-%m-%d").ok()
+}
+
+/// First day strictly after `from` falling on `weekday`.
+fn next_weekday(from: NaiveDate, weekday: Weekday) -> Option<NaiveDate> {
+    let ahead = (weekday.num_days_from_monday() + 7 - from.weekday().num_days_from_monday()) % 7;
+    from.checked_add_days(Days::new(if ahead == 0 { 7 } else { ahead as u64 }))
+}
+
+/// Number of days in `3d` or `2w`.
+pub fn offset_days(s: &str) -> Option<i64> {
+    if !s.is_ascii() {
+        return None;
+    }
+    let (n, unit) = s.split_at(s.len().checked_sub(1)?);
+    let n = n.parse::<i64>().ok()?;
+    match unit {
+        "d" => Some(n),
+        "w" => n.checked_mul(7),
+        _ => None,
+    }
+}
+
+pub fn weekday(s: &str) -> Option<Weekday> {
+    match s {
+        "mon" | "monday" => Some(Weekday::Mon),
+        "tue" | "tuesday" => Some(Weekday::Tue),
+        "wed" | "wednesday" => Some(Weekday::Wed),
+        "thu" | "thursday" => Some(Weekday::Thu),
+        "fri" | "friday" => Some(Weekday::Fri),
+        "sat" | "saturday" => Some(Weekday::Sat),
+        "sun" | "sunday" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// How often a todo comes back once it is ticked off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Recurrence {
+    Daily,
+    Weekly(Weekday),
+    Monthly,
+    /// every that many days
+    Every(u32),
+}
+
+impl Recurrence {
+    /// Like `from_str`, additionally `weekly` means weekly on today's weekday.
+    pub fn parse(s: &str, today: NaiveDate) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "weekly" => Some(Recurrence::Weekly(today.weekday())),
+            s => s.parse().ok(),
+        }
+    }
+
+    /// The date after `from`, none past the last date there is.
+    pub fn next(self, from: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Recurrence::Daily => from.succ_opt(),
+            Recurrence::Weekly(weekday) => next_weekday(from, weekday),
+            Recurrence::Monthly => from.checked_add_months(Months::new(1)),
+            Recurrence::Every(days) => from.checked_add_days(Days::new(days.into())),
+        }
+    }
+
+    /// Next due date after `today`, keeping to the schedule set by `due`.
+    pub fn next_due(self, due: Option<NaiveDate>, today: NaiveDate) -> Option<NaiveDate> {
+        let mut next = self.next(due.unwrap_or(today))?;
+        while next <= today {
+            next = self.next(next)?;
+        }
+        Some(next)
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        match s.as_str() {
+            "daily" => Ok(Recurrence::Daily),
+            "monthly" => Ok(Recurrence::Monthly),
+            _ => weekday(&s)
+                .map(Recurrence::Weekly)
+                .or_else(|| {
+                    offset_days(&s)
+                        .and_then(|days| u32::try_from(days).ok())
+                        .filter(|days| *days > 0)
+                        .map(Recurrence::Every)
+                })
+                .ok_or(format!("invalid recurrence {s}")),
+        }
+    }
+}
+
+impl TryFrom<String> for Recurrence {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Recurrence> for String {
+    fn from(recurrence: Recurrence) -> Self {
+        recurrence.to_string()
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Recurrence::Daily => write!(f, "daily"),
+            Recurrence::Weekly(weekday) => write!(f, "{}", weekday.to_string().to_lowercase()),
+            Recurrence::Monthly => write!(f, "monthly"),
+            Recurrence::Every(days) => write!(f, "{days}d"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shortcuts() {
+        // a wednesday
+        let today = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+        let day = |d| NaiveDate::from_ymd_opt(2026, 10, d);
+        assert_eq!(parse("today", today), day(14));
+        assert_eq!(parse("Tomorrow", today), day(15));
+        assert_eq!(parse("+3d", today), day(17));
+        assert_eq!(parse("+1w", today), day(21));
+        assert_eq!(parse("fri", today), day(16));
+        assert_eq!(parse("wed", today), day(21));
+        assert_eq!(
+            parse("2026-11-01", today),
+            NaiveDate::from_ymd_opt(2026, 11, 1)
+        );
+        assert_eq!(parse("someday", today), None);
+        assert_eq!(parse("+99999999999d", today), None);
+        assert_eq!(parse("+9999999999999999w", today), None);
+    }
+
+    #[test]
+    fn recurrences() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+        let day = |m, d| NaiveDate::from_ymd_opt(2026, m, d).unwrap();
+        let rec = |s| Recurrence::parse(s, today).unwrap();
+
+        assert_eq!(rec("daily").next_due(None, today), Some(day(10, 15)));
+        assert_eq!(rec("weekly"), Recurrence::Weekly(Weekday::Wed));
+        assert_eq!(rec("mon").next_due(None, today), Some(day(10, 19)));
+        assert_eq!(
+            rec("3d").next_due(Some(day(10, 1)), today),
+            Some(day(10, 16))
+        );
+        assert_eq!(
+            rec("monthly").next_due(Some(day(10, 1)), today),
+            Some(day(11, 1))
+        );
+        assert_eq!(
+            rec("monthly").next_due(Some(day(1, 31)), day(1, 31)),
+            Some(day(2, 28))
+        );
+        assert_eq!(rec("fri").to_string().parse::<Recurrence>(), Ok(rec("fri")));
+        assert!(Recurrence::parse("0d", today).is_none());
+        assert!(Recurrence::parse("4294967296d", today).is_none());
+        assert_eq!(rec("4294967295d").next_due(None, today), None);
+        assert_eq!(rec("daily").next_due(Some(NaiveDate::MAX), today), None);
+    }
+}
@@ -0,0 +1,233 @@
+//! Multi-line editor for notes, following the row and scroll handling of the
+//! editor in `go22dos_c`.
+use ncurses::*;
+
+use crate::config;
+
+pub struct Editor {
+    rows: Vec<Vec<char>>,
+    c_x: usize,
+    c_y: usize,
+    row_offset: usize,
+    col_offset: usize,
+    /// size of the screen area the editor draws to
+    screen_rows: usize,
+    screen_cols: usize,
+}
+
+impl Editor {
+    pub fn new(text: &str, screen_rows: usize, screen_cols: usize) -> Self {
+        Self {
+            rows: text.lines().map(|line| line.chars().collect()).collect(),
+            c_x: 0,
+            c_y: 0,
+            row_offset: 0,
+            col_offset: 0,
+            screen_rows: screen_rows.max(1),
+            screen_cols: screen_cols.max(1),
+        }
+    }
+
+    /// The edited text, without trailing blank lines.
+    pub fn text(&self) -> String {
+        self.rows
+            .iter()
+            .map(|row| row.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim_end()
+            .to_owned()
+    }
+
+    fn scroll(&mut self) {
+        if self.c_y < self.row_offset {
+            self.row_offset = self.c_y;
+        }
+        if self.c_y >= self.row_offset + self.screen_rows {
+            self.row_offset = self.c_y - self.screen_rows + 1;
+        }
+
+        if self.c_x < self.col_offset {
+            self.col_offset = self.c_x;
+        }
+        if self.c_x >= self.col_offset + self.screen_cols {
+            self.col_offset = self.c_x - self.screen_cols + 1;
+        }
+    }
+
+    pub fn draw(&mut self) {
+        self.scroll();
+        (0..self.screen_rows).for_each(|i| {
+            let file_row = i + self.row_offset;
+            mv(i as i32, 0);
+            clrtoeol();
+            match self.rows.get(file_row) {
+                Some(row) => {
+                    let line = row
+                        .iter()
+                        .skip(self.col_offset)
+                        .take(self.screen_cols)
+                        .collect::<String>();
+                    addstr(&line);
+                }
+                None => {
+                    attron(COLOR_PAIR(config::OTHER_PAIR));
+                    addstr("~");
+                    attroff(COLOR_PAIR(config::OTHER_PAIR));
+                }
+            }
+        });
+        mv(
+            (self.c_y - self.row_offset) as i32,
+            (self.c_x - self.col_offset) as i32,
+        );
+    }
+
+    /// Handles a key, `false` once editing is done.
+    pub fn process(&mut self, c: i32) -> bool {
+        match c {
+            config::ESC => return false,
+            config::ENTER => self.insert_newline(),
+            config::REMOVE | KEY_BACKSPACE => self.delete_char(),
+            KEY_HOME => self.c_x = 0,
+            KEY_END => self.c_x = self.rows.get(self.c_y).map_or(0, |row| row.len()),
+            KEY_PPAGE | KEY_NPAGE => {
+                if c == KEY_PPAGE {
+                    self.c_y = self.row_offset;
+                } else {
+                    self.c_y = (self.row_offset + self.screen_rows - 1).min(self.rows.len());
+                }
+                (0..self.screen_rows).for_each(|_| {
+                    self.move_cursor(match c {
+                        KEY_PPAGE => KEY_UP,
+                        _ => KEY_DOWN,
+                    })
+                });
+            }
+            KEY_UP | KEY_DOWN | KEY_LEFT | KEY_RIGHT => self.move_cursor(c),
+            c if (32..127).contains(&c) => self.insert_char(c as u8 as char),
+            _ => (),
+        }
+        true
+    }
+
+    fn move_cursor(&mut self, key: i32) {
+        let len = |editor: &Self| editor.rows.get(editor.c_y).map(|row| row.len());
+
+        match key {
+            KEY_LEFT => {
+                if self.c_x != 0 {
+                    self.c_x -= 1;
+                } else if self.c_y > 0 {
+                    self.c_y -= 1;
+                    self.c_x = len(self).unwrap_or(0);
+                }
+            }
+            KEY_RIGHT => match len(self) {
+                Some(n) if self.c_x < n => self.c_x += 1,
+                Some(_) => {
+                    self.c_y += 1;
+                    self.c_x = 0;
+                }
+                None => (),
+            },
+            KEY_UP => self.c_y = self.c_y.saturating_sub(1),
+            KEY_DOWN if self.c_y < self.rows.len() => self.c_y += 1,
+            _ => (),
+        }
+
+        self.c_x = self.c_x.min(len(self).unwrap_or(0));
+    }
+
+    fn insert_char(&mut self, c: char) {
+        if self.c_y == self.rows.len() {
+            self.rows.push(vec![]);
+        }
+        self.rows[self.c_y].insert(self.c_x, c);
+        self.c_x += 1;
+    }
+
+    fn insert_newline(&mut self) {
+        match self.rows.get_mut(self.c_y) {
+            Some(row) => {
+                let rest = row.split_off(self.c_x);
+                self.rows.insert(self.c_y + 1, rest);
+            }
+            None => self.rows.push(vec![]),
+        }
+        self.c_y += 1;
+        self.c_x = 0;
+    }
+
+    fn delete_char(&mut self) {
+        if self.c_y == self.rows.len() && self.c_y > 0 {
+            self.c_y -= 1;
+            self.c_x = self.rows[self.c_y].len();
+            return;
+        }
+        match (self.c_x, self.c_y) {
+            (0, 0) => (),
+            (0, _) => {
+                // join the row with the one above
+                let row = self.rows.remove(self.c_y);
+                self.c_y -= 1;
+                self.c_x = self.rows[self.c_y].len();
+                self.rows[self.c_y].extend(row);
+            }
+            _ => {
+                self.c_x -= 1;
+                self.rows[self.c_y].remove(self.c_x);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn typed(editor: &mut Editor, s: &str) {
+        s.chars().for_each(|c| {
+            editor.process(match c {
+                '\n' => config::ENTER,
+                c => c as i32,
+            });
+        });
+    }
+
+    #[test]
+    fn editing() {
+        let mut editor = Editor::new("", 2, 10);
+        typed(&mut editor, "first\nsecond");
+        assert_eq!(editor.text(), "first\nsecond");
+
+        editor.process(KEY_UP);
+        editor.process(KEY_END);
+        editor.process(config::REMOVE);
+        editor.process(KEY_DOWN);
+        editor.process(KEY_HOME);
+        editor.process(config::REMOVE);
+        assert_eq!(editor.text(), "firssecond");
+
+        editor.process(KEY_END);
+        typed(&mut editor, "\n\nthird");
+        assert_eq!(editor.text(), "firssecond\n\nthird");
+        assert!(!editor.process(config::ESC));
+    }
+
+    #[test]
+    fn scrolling() {
+        let mut editor = Editor::new("a\nb\nc\nd\n0123456789abc", 2, 10);
+        (0..4).for_each(|_| {
+            editor.process(KEY_DOWN);
+        });
+        editor.process(KEY_END);
+        editor.scroll();
+        assert_eq!((editor.row_offset, editor.col_offset), (3, 4));
+
+        editor.process(KEY_PPAGE);
+        editor.scroll();
+        assert_eq!(editor.c_y, 1);
+        assert_eq!(editor.row_offset, 1);
+    }
+}
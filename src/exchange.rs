@@ -0,0 +1,109 @@
+//! Reading and writing todos in the formats of other tools, picked by the
+//! extension of the file.
+use std::fmt;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+use crate::ical;
+use crate::item::Item;
+use crate::markdown;
+use crate::org;
+use crate::table;
+use crate::taskwarrior;
+use crate::todotxt;
+
+/// Topics with their items, in order.
+pub type Topics = Vec<(String, Vec<Item>)>;
+
+#[derive(Debug, Error)]
+pub enum ExchangeError {
+    IoError(#[from] std::io::Error),
+    UnknownFormat(String),
+}
+impl fmt::Display for ExchangeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+type Result<T> = std::result::Result<T, ExchangeError>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Markdown,
+    TodoTxt,
+    ICalendar,
+    Org,
+    Csv,
+    Taskwarrior,
+}
+
+impl Format {
+    pub fn of(path: &str) -> Result<Self> {
+        let ext = Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        match ext.as_str() {
+            "md" | "markdown" => Ok(Format::Markdown),
+            "txt" => Ok(Format::TodoTxt),
+            "ics" | "ical" => Ok(Format::ICalendar),
+            "org" => Ok(Format::Org),
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Taskwarrior),
+            _ => Err(ExchangeError::UnknownFormat(path.to_owned())),
+        }
+    }
+}
+
+/// Name of the file without directories and extension, for todos which do
+/// not say which topic they belong to.
+fn stem(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(path)
+        .to_owned()
+}
+
+/// File next to `path` for one of the topics, `todos-work.ics` for
+/// `todos.ics`.
+pub fn split(path: &str, topic: &str) -> String {
+    let path = Path::new(path);
+    let topic = topic.replace(['/', '\\'], "-");
+    let name = match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => format!("{}-{topic}.{ext}", stem(&path.to_string_lossy())),
+        None => format!("{}-{topic}", stem(&path.to_string_lossy())),
+    };
+    path.with_file_name(name).to_string_lossy().into_owned()
+}
+
+/// The topics read, along with what had to be left out of them.
+pub fn import(path: &str, at: DateTime<Utc>) -> Result<(Topics, Vec<String>)> {
+    let format = Format::of(path)?;
+    let contents = std::fs::read_to_string(path)?;
+    let topic = stem(path);
+    Ok(match format {
+        Format::Markdown => (markdown::import(&contents, &topic, at), vec![]),
+        Format::TodoTxt => (todotxt::import(&contents, &topic, at), vec![]),
+        Format::ICalendar => (ical::import(&contents, &topic, at), vec![]),
+        Format::Org => (org::import(&contents, &topic, at), vec![]),
+        Format::Csv => table::import(&contents, &topic, at),
+        Format::Taskwarrior => taskwarrior::import(&contents, &topic, at),
+    })
+}
+
+pub fn export(path: &str, topics: &[(String, Vec<Item>)]) -> Result<()> {
+    let contents = match Format::of(path)? {
+        Format::Markdown => markdown::export(topics),
+        Format::TodoTxt => todotxt::export(topics),
+        Format::ICalendar => ical::export(topics),
+        Format::Org => org::export(topics),
+        Format::Csv => table::export(topics),
+        Format::Taskwarrior => taskwarrior::export(topics),
+    };
+    std::fs::write(path, contents).map_err(ExchangeError::IoError)
+}
@@ -0,0 +1,457 @@
+//! Items as iCalendar (RFC 5545) VTODOs. Topics go to the first of the
+//! `CATEGORIES`, tags to the others, and subtasks are `RELATED-TO` their
+//! parent:
+//!
+//! ```text
+//! BEGIN:VTODO
+//! UID:1791100800000000000@go22dos
+//! SUMMARY:call mom #phone
+//! CATEGORIES:family,phone
+//! STATUS:NEEDS-ACTION
+//! DUE;VALUE=DATE:20261020
+//! END:VTODO
+//! ```
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+
+use crate::date::Recurrence;
+use crate::item::{Item, Priority};
+
+const DATE_TIME: &str = "%Y%m%dT%H%M%SZ";
+const DATE: &str = "%Y%m%d";
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn unescape(s: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match (c, c == '\\') {
+            (_, true) => match chars.next() {
+                Some('n' | 'N') => unescaped.push('\n'),
+                Some(c) => unescaped.push(c),
+                None => (),
+            },
+            (c, _) => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+/// Splits a list value at commas which are not escaped.
+fn values(s: &str) -> Vec<String> {
+    let mut values = vec![String::new()];
+    let mut escaped = false;
+    for c in s.chars() {
+        match (c, escaped) {
+            (',', false) => values.push(String::new()),
+            _ => values
+                .last_mut()
+                .into_iter()
+                .for_each(|value| value.push(c)),
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    values
+        .iter()
+        .map(|value| unescape(value).trim().to_owned())
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+/// Content lines are folded after 75 octets, continuing with a space.
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded + "\r\n"
+}
+
+fn rrule(recurrence: Recurrence) -> String {
+    match recurrence {
+        Recurrence::Daily => "FREQ=DAILY".to_owned(),
+        Recurrence::Weekly(weekday) => {
+            let day = weekday.to_string().to_uppercase();
+            format!("FREQ=WEEKLY;BYDAY={}", &day[..2])
+        }
+        Recurrence::Monthly => "FREQ=MONTHLY".to_owned(),
+        Recurrence::Every(days) => format!("FREQ=DAILY;INTERVAL={days}"),
+    }
+}
+
+fn recurrence(rrule: &str) -> Option<Recurrence> {
+    let parts = rrule
+        .split(';')
+        .filter_map(|part| part.split_once('='))
+        .collect::<HashMap<_, _>>();
+    let interval = parts
+        .get("INTERVAL")
+        .and_then(|n| n.parse::<u32>().ok())
+        .unwrap_or(1);
+    let weekday = parts.get("BYDAY").and_then(|day| {
+        [
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+            Weekday::Sat,
+            Weekday::Sun,
+        ]
+        .into_iter()
+        .find(|weekday| weekday.to_string().to_uppercase().starts_with(*day))
+    });
+    match (parts.get("FREQ").copied()?, interval, weekday) {
+        ("DAILY", 1, _) => Some(Recurrence::Daily),
+        ("DAILY", n, _) => Some(Recurrence::Every(n)),
+        ("WEEKLY", 1, Some(weekday)) => Some(Recurrence::Weekly(weekday)),
+        ("WEEKLY", n, _) => Some(Recurrence::Every(7 * n)),
+        ("MONTHLY", _, _) => Some(Recurrence::Monthly),
+        _ => None,
+    }
+}
+
+/// `A` to `I` are priorities 1 to 9, anything lower is 9.
+fn priority(priority: Priority) -> u8 {
+    (priority.letter() as u8 - b'A' + 1).min(9)
+}
+
+fn write_todos(
+    items: &[Item],
+    topic: &str,
+    parent: Option<&str>,
+    uids: &mut HashSet<String>,
+    lines: &mut Vec<String>,
+) {
+    items.iter().for_each(|item| {
+        let uid = item
+            .created
+            .and_then(|created| created.timestamp_nanos_opt())
+            .map(|nanos| format!("{nanos}@go22dos"))
+            .filter(|uid| !uids.contains(uid))
+            .unwrap_or(format!("{}@go22dos", uids.len()));
+        uids.insert(uid.clone());
+        let stamp = item.modified.or(item.created).unwrap_or(Utc::now());
+
+        lines.push("BEGIN:VTODO".to_owned());
+        lines.push(format!("UID:{uid}"));
+        lines.push(format!("DTSTAMP:{}", stamp.format(DATE_TIME)));
+        lines.push(format!("SUMMARY:{}", escape(item.text())));
+        let categories = std::iter::once(topic)
+            .chain(item.tags())
+            .map(escape)
+            .collect::<Vec<_>>();
+        lines.push(format!("CATEGORIES:{}", categories.join(",")));
+        if !item.notes.is_empty() {
+            lines.push(format!("DESCRIPTION:{}", escape(&item.notes)));
+        }
+        lines.push(match item.is_done() {
+            true => "STATUS:COMPLETED".to_owned(),
+            _ => "STATUS:NEEDS-ACTION".to_owned(),
+        });
+        let times = [
+            ("CREATED", item.created),
+            ("LAST-MODIFIED", item.modified),
+            ("COMPLETED", item.completed),
+        ];
+        times.iter().for_each(|(name, at)| {
+            lines.extend(at.map(|at| format!("{name}:{}", at.format(DATE_TIME))))
+        });
+        lines.extend(
+            item.due
+                .map(|due| format!("DUE;VALUE=DATE:{}", due.format(DATE))),
+        );
+        lines.extend(item.priority.map(|p| format!("PRIORITY:{}", priority(p))));
+        lines.extend(item.recurrence.map(|r| format!("RRULE:{}", rrule(r))));
+        lines.extend(parent.map(|parent| format!("RELATED-TO;RELTYPE=PARENT:{parent}")));
+        lines.push("END:VTODO".to_owned());
+
+        write_todos(&item.children, topic, Some(&uid), uids, lines);
+    });
+}
+
+/// One calendar of all topics, named after the topic if there is only one.
+pub fn export(topics: &[(String, Vec<Item>)]) -> String {
+    let name = match topics {
+        [(topic, _)] => topic.as_str(),
+        _ => "go22dos",
+    };
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        "PRODID:-//go22dos//go22dos//EN".to_owned(),
+        format!("X-WR-CALNAME:{}", escape(name)),
+    ];
+    let mut uids = HashSet::new();
+    topics
+        .iter()
+        .for_each(|(topic, items)| write_todos(items, topic, None, &mut uids, &mut lines));
+    lines.push("END:VCALENDAR".to_owned());
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+/// A date-time in UTC, or else in local time, or a date at midnight.
+fn time(s: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(s, DATE_TIME)
+        .map(|time| time.and_utc())
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(s, "%Y%m%dT%H%M%S")
+                .ok()
+                .and_then(|time| Local.from_local_datetime(&time).earliest())
+                .map(|time| time.with_timezone(&Utc))
+        })
+        .or_else(|| {
+            date(s)
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .map(|time| time.and_utc())
+        })
+}
+
+fn date(s: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(s.get(..8)?, DATE).ok()
+}
+
+/// A VTODO as it was read: topic, uid, parent uid and the item.
+struct Todo {
+    topic: String,
+    uid: Option<String>,
+    parent: Option<String>,
+    item: Item,
+}
+
+/// A content line, its name, parameters and value.
+type Property = (String, String, String);
+
+fn todo(properties: &[Property], topic: &str, at: DateTime<Utc>) -> Todo {
+    let get = |name: &str| {
+        properties
+            .iter()
+            .find(|(n, _, _)| n == name)
+            .map(|(_, _, value)| value.as_str())
+    };
+
+    let mut item = Item::new("", at);
+    // the text is taken as is, not for any `due:` it might contain
+    item.entry = format!("0{}", get("SUMMARY").map(unescape).unwrap_or_default());
+    item.notes = get("DESCRIPTION").map(unescape).unwrap_or_default();
+    item.created = get("CREATED")
+        .and_then(time)
+        .or(get("DTSTAMP").and_then(time));
+    item.modified = get("LAST-MODIFIED").and_then(time);
+    item.due = get("DUE").and_then(date);
+    item.priority = get("PRIORITY")
+        .and_then(|p| p.trim().parse::<u8>().ok())
+        .filter(|p| (1..=9).contains(p))
+        .and_then(|p| Priority::try_from((b'A' + p - 1) as char).ok());
+    item.recurrence = get("RRULE").and_then(recurrence);
+    let completed = get("COMPLETED").and_then(time);
+    if completed.is_some() || matches!(get("STATUS"), Some("COMPLETED" | "CANCELLED")) {
+        item.entry = format!("1{}", item.text());
+        item.completed = completed.or(item.modified).or(Some(at));
+    }
+
+    let mut categories = properties
+        .iter()
+        .filter(|(name, _, _)| name == "CATEGORIES")
+        .flat_map(|(_, _, value)| values(value));
+    let topic = categories.next().unwrap_or(topic.to_owned());
+    let tags = categories
+        .map(|category| category.replace(' ', "-"))
+        .filter(|tag| !item.has_tag(tag))
+        .map(|tag| format!(" #{tag}"))
+        .collect::<String>();
+    item.entry.push_str(&tags);
+
+    Todo {
+        topic,
+        uid: get("UID").map(str::to_owned),
+        parent: properties
+            .iter()
+            .find(|(name, params, _)| {
+                name == "RELATED-TO"
+                    && params.split(';').all(|param| {
+                        !param.to_uppercase().starts_with("RELTYPE=")
+                            || param.eq_ignore_ascii_case("RELTYPE=PARENT")
+                    })
+            })
+            .map(|(_, _, value)| value.clone()),
+        item,
+    }
+}
+
+/// Subtasks of the todo with `uid`, or the todos without a known parent.
+fn tree(
+    todos: &mut Vec<Todo>,
+    topic: &str,
+    uid: Option<&str>,
+    uids: &HashSet<(String, String)>,
+) -> Vec<Item> {
+    let is_child = |todo: &Todo| {
+        todo.topic == topic
+            && match uid {
+                Some(uid) => todo.parent.as_deref() == Some(uid),
+                None => todo
+                    .parent
+                    .as_ref()
+                    .is_none_or(|parent| !uids.contains(&(topic.to_owned(), parent.clone()))),
+            }
+    };
+    let mut children = vec![];
+    while let Some(i) = todos.iter().position(is_child) {
+        children.push(take(todos, i, topic, uids));
+    }
+    children
+}
+
+/// Removes the `i`th todo from `todos` along with its subtasks.
+fn take(todos: &mut Vec<Todo>, i: usize, topic: &str, uids: &HashSet<(String, String)>) -> Item {
+    let todo = todos.remove(i);
+    let mut item = todo.item;
+    if let Some(uid) = todo.uid.as_deref() {
+        item.children = tree(todos, topic, Some(uid), uids);
+    }
+    item
+}
+
+/// Todos without categories go into the topic named by the calendar, or else
+/// into `topic`.
+pub fn import(s: &str, topic: &str, at: DateTime<Utc>) -> Vec<(String, Vec<Item>)> {
+    let unfolded = s.lines().fold(Vec::<String>::new(), |mut lines, line| {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_owned()),
+        }
+        lines
+    });
+
+    let mut calendar = topic.to_owned();
+    let mut properties: Option<Vec<Property>> = None;
+    let mut todos = vec![];
+    unfolded.iter().for_each(|line| {
+        let Some((key, value)) = line.split_once(':') else {
+            return;
+        };
+        let (name, params) = key.split_once(';').unwrap_or((key, ""));
+        let name = name.to_uppercase();
+        match (name.as_str(), value.trim().to_uppercase().as_str()) {
+            ("BEGIN", "VTODO") => properties = Some(vec![]),
+            ("END", "VTODO") => {
+                todos.extend(properties.take().map(|p| todo(&p, &calendar, at)));
+            }
+            ("X-WR-CALNAME", _) if properties.is_none() => calendar = unescape(value.trim()),
+            _ => properties.iter_mut().for_each(|p| {
+                p.push((name.clone(), params.to_owned(), value.trim_end().to_owned()))
+            }),
+        }
+    });
+
+    let mut topics: Vec<String> = vec![];
+    todos.iter().for_each(|todo| {
+        if !topics.contains(&todo.topic) {
+            topics.push(todo.topic.clone());
+        }
+    });
+    let uids = todos
+        .iter()
+        .filter_map(|todo| Some((todo.topic.clone(), todo.uid.clone()?)))
+        .collect::<HashSet<_>>();
+    topics
+        .into_iter()
+        .map(|topic| {
+            let mut items = tree(&mut todos, &topic, None, &uids);
+            // related to themselves, or to each other in a cycle
+            while let Some(i) = todos.iter().position(|todo| todo.topic == topic) {
+                items.push(take(&mut todos, i, &topic, &uids));
+            }
+            (topic, items)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn calendars() {
+        let at = Utc::now();
+        let mut a = Item::new("call mom, then #dad due:2026-10-20 rec:fri", at);
+        a.notes = "about\nthe weekend".to_owned();
+        a.priority = Priority::try_from('B').ok();
+        let mut b = Item::new("subtask", at + chrono::Duration::seconds(1));
+        b.toggle(at);
+        a.children = vec![b];
+        let topics = vec![
+            ("family".to_owned(), vec![a]),
+            ("work; misc".to_owned(), vec![Item::new("report", at)]),
+        ];
+
+        let s = export(&topics);
+        assert!(s.contains("SUMMARY:call mom\\, then #dad\r\n"));
+        assert!(s.contains("RRULE:FREQ=WEEKLY;BYDAY=FR\r\n"));
+        assert!(s.contains("CATEGORIES:family,dad\r\n"));
+        assert!(s.lines().all(|line| line.len() <= 75));
+
+        let imported = import(&s, "calendar", at);
+        let mut expected = topics.clone();
+        // timestamps lose their fractions of a second
+        fn truncated(items: &mut [Item]) {
+            items.iter_mut().for_each(|item| {
+                for at in [&mut item.created, &mut item.modified, &mut item.completed] {
+                    *at = at.and_then(|at| time(&at.format(DATE_TIME).to_string()));
+                }
+                truncated(&mut item.children);
+            })
+        }
+        expected.iter_mut().for_each(|(_, items)| truncated(items));
+        assert_eq!(imported, expected);
+    }
+
+    #[test]
+    fn foreign_todos() {
+        let at = Utc::now();
+        let s = "BEGIN:VCALENDAR\nX-WR-CALNAME:Chores\nBEGIN:VTODO\nUID:2\n\
+                 RELATED-TO:1\nSUMMARY:sub\nEND:VTODO\nBEGIN:VTODO\nUID:1\nSUMMARY:long\n \
+                 er text\nCATEGORIES:house work,garden\nSTATUS:COMPLETED\nDUE;TZID=X:20261020T100000\n\
+                 PRIORITY:0\nEND:VTODO\nBEGIN:VTODO\nSUMMARY:loose\nEND:VTODO\nEND:VCALENDAR\n";
+        let topics = import(s, "file", at);
+        assert_eq!(topics[0].0, "Chores");
+        assert_eq!(topics[1].0, "house work");
+        let long = &topics[1].1[0];
+        assert_eq!(long.text(), "longer text #garden");
+        assert!(long.is_done());
+        assert_eq!(long.due, NaiveDate::from_ymd_opt(2026, 10, 20));
+        assert_eq!(long.priority, None);
+        // the parent of sub is in another topic
+        assert_eq!(topics[0].1[0].text(), "sub");
+        assert_eq!(topics[0].1[1].text(), "loose");
+    }
+
+    #[test]
+    fn cyclic_todos() {
+        let s = "BEGIN:VTODO\nUID:1\nRELATED-TO:1\nSUMMARY:self\nEND:VTODO\n\
+                 BEGIN:VTODO\nUID:2\nRELATED-TO:3\nSUMMARY:a\nEND:VTODO\n\
+                 BEGIN:VTODO\nUID:3\nRELATED-TO:2\nSUMMARY:b\nEND:VTODO\n";
+        let topics = import(s, "file", Utc::now());
+        let items = &topics[0].1;
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].text(), "self");
+        assert!(items[0].children.is_empty());
+        assert_eq!(items[1].text(), "a");
+        assert_eq!(items[1].children[0].text(), "b");
+    }
+}
@@ -0,0 +1,580 @@
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::de::{self, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+use crate::date::{self, Recurrence};
+
+/// Priority from `A` (highest) to `Z`, the way todo.txt has it. Raising an
+/// item without priority starts at `C`, lowering an item from `Z` clears it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "char", into = "char")]
+pub struct Priority(char);
+
+impl Priority {
+    pub const LOW: Priority = Priority('C');
+
+    pub fn raise(priority: Option<Self>) -> Option<Self> {
+        match priority {
+            None => Some(Self::LOW),
+            Some(Priority('A')) => priority,
+            Some(Priority(c)) => Some(Priority((c as u8 - 1) as char)),
+        }
+    }
+
+    pub fn lower(priority: Option<Self>) -> Option<Self> {
+        match priority {
+            Some(Priority(c)) if c < 'Z' => Some(Priority((c as u8 + 1) as char)),
+            _ => None,
+        }
+    }
+
+    pub fn letter(self) -> char {
+        self.0
+    }
+}
+
+impl TryFrom<char> for Priority {
+    type Error = String;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c.is_ascii_uppercase() {
+            true => Ok(Priority(c)),
+            _ => Err(format!("invalid priority {c}")),
+        }
+    }
+}
+
+impl From<Priority> for char {
+    fn from(priority: Priority) -> Self {
+        priority.0
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A todo. The entry keeps the layout items had before they carried any
+/// metadata: a status character (`0` open, `1` done) followed by the text.
+///
+/// Text typed in for an item may carry `key:value` tokens which are split off
+/// into fields, e.g. `due:tomorrow` or `rec:weekly`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(remote = "Self")]
+pub struct Item {
+    pub entry: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<NaiveDate>,
+    /// When work on the item is planned to start, as in org-mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduled: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<Recurrence>,
+    /// When a recurring item has been ticked off.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub completions: Vec<DateTime<Utc>>,
+    /// Longer text going with an item, possibly spanning several lines.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+    /// Subtasks, nested arbitrarily deep.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Item>,
+    /// Whether the subtasks are hidden in the items view.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub collapsed: bool,
+}
+
+impl Item {
+    pub fn new(text: impl AsRef<str>, at: DateTime<Utc>) -> Self {
+        let mut item = Self {
+            entry: "0".to_owned(),
+            created: Some(at),
+            modified: None,
+            completed: None,
+            due: None,
+            scheduled: None,
+            priority: None,
+            recurrence: None,
+            completions: vec![],
+            notes: String::new(),
+            children: vec![],
+            collapsed: false,
+        };
+        item.edit(text, at);
+        item
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.entry.starts_with('1')
+    }
+
+    pub fn text(&self) -> &str {
+        self.entry.get(1..).unwrap_or_default()
+    }
+
+    /// Ticks an item off or reopens it. Ticking off a recurring item records
+    /// the completion and keeps it open with its next due date.
+    pub fn toggle(&mut self, at: DateTime<Utc>) {
+        if let (false, Some(recurrence)) = (self.is_done(), self.recurrence) {
+            let today = at.with_timezone(&Local).date_naive();
+            self.completions.push(at);
+            let due = recurrence.next_due(self.due, today).or(self.due);
+            // a schedule repeating along with the due date moves on with it
+            if self
+                .scheduled
+                .is_some_and(|scheduled| Some(scheduled) == self.due)
+            {
+                self.scheduled = due;
+            }
+            self.due = due;
+            self.modified = Some(at);
+            return;
+        }
+        let (status, completed) = match self.is_done() {
+            true => ('0', None),
+            _ => ('1', Some(at)),
+        };
+        self.entry = format!("{status}{}", self.text());
+        self.completed = completed;
+        self.modified = Some(at);
+    }
+
+    pub fn edit(&mut self, text: impl AsRef<str>, at: DateTime<Utc>) {
+        let today = at.with_timezone(&Local).date_naive();
+        let mut words = vec![];
+        for word in text.as_ref().split(' ') {
+            match word.split_once(':') {
+                // an empty value clears the field
+                Some(("due", "")) => self.due = None,
+                Some(("due", due)) => match date::parse(due, today) {
+                    Some(due) => self.due = Some(due),
+                    None => words.push(word),
+                },
+                Some(("rec", "")) => self.recurrence = None,
+                Some(("rec", rec)) => match Recurrence::parse(rec, today) {
+                    Some(rec) => self.recurrence = Some(rec),
+                    None => words.push(word),
+                },
+                _ => words.push(word),
+            }
+        }
+        let status = self.entry.chars().next().unwrap_or('0');
+        self.entry = format!("{status}{}", words.join(" ").trim_end());
+        self.modified = Some(at);
+    }
+
+    pub fn annotate(&mut self, notes: impl Into<String>, at: DateTime<Utc>) {
+        let notes = notes.into();
+        if notes != self.notes {
+            self.notes = notes;
+            self.modified = Some(at);
+        }
+    }
+
+    /// The text with its `due:` and `rec:` tokens, as it would be typed in.
+    pub fn line(&self) -> String {
+        let mut line = self.text().to_owned();
+        if let Some(due) = self.due {
+            line.push_str(&format!(" due:{due}"));
+        }
+        if let Some(recurrence) = self.recurrence {
+            line.push_str(&format!(" rec:{recurrence}"));
+        }
+        line
+    }
+
+    /// Takes over an edited `line`, tokens left out clear their fields.
+    pub fn rewrite(&mut self, line: &str, at: DateTime<Utc>) {
+        self.due = None;
+        self.recurrence = None;
+        self.edit(line.trim(), at);
+    }
+
+    /// The item as a file to edit: its line first, the notes below a blank
+    /// line.
+    pub fn document(&self) -> String {
+        match self.notes.is_empty() {
+            true => format!("{}\n", self.line()),
+            _ => format!("{}\n\n{}\n", self.line(), self.notes),
+        }
+    }
+
+    /// Takes over an edited `document`.
+    pub fn revise(&mut self, document: &str, at: DateTime<Utc>) {
+        let (line, notes) = document.split_once('\n').unwrap_or((document, ""));
+        self.rewrite(line, at);
+        self.notes = notes.trim().to_owned();
+    }
+
+    pub fn raise(&mut self, at: DateTime<Utc>) {
+        self.priority = Priority::raise(self.priority);
+        self.modified = Some(at);
+    }
+
+    pub fn lower(&mut self, at: DateTime<Utc>) {
+        self.priority = Priority::lower(self.priority);
+        self.modified = Some(at);
+    }
+
+    /// Whether both are the same item, however else either was changed.
+    /// Items created at once, e.g. by an import, are told apart by their text,
+    /// so an item whose text changed is taken for another one.
+    pub fn is(&self, other: &Self) -> bool {
+        self.created == other.created && self.text() == other.text()
+    }
+
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        !self.is_done() && self.due.is_some_and(|due| due < today)
+    }
+
+    /// Tags are the words of the text starting with `#`, e.g. `#urgent`.
+    pub fn tags(&self) -> Vec<&str> {
+        self.text()
+            .split(' ')
+            .filter(|word| is_tag(word))
+            .map(|word| &word[1..])
+            .collect()
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let tag = tag.trim_start_matches('#');
+        self.tags().iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    /// How far along an item is: done items count fully, open items with
+    /// subtasks by the average progress of their subtasks.
+    pub fn progress(&self) -> f32 {
+        match (self.is_done(), self.children.is_empty()) {
+            (true, _) => 1.0,
+            (_, true) => 0.0,
+            _ => self.children.iter().map(Item::progress).sum::<f32>() / self.children.len() as f32,
+        }
+    }
+}
+
+pub fn is_tag(word: &str) -> bool {
+    word.len() > 1 && word.starts_with('#')
+}
+
+/// Items are addressed by their path through the tree of subtasks, `[2, 0]`
+/// being the first subtask of the third item.
+pub fn get<'a>(items: &'a [Item], path: &[usize]) -> Option<&'a Item> {
+    let (first, rest) = path.split_first()?;
+    let item = items.get(*first)?;
+    match rest.is_empty() {
+        true => Some(item),
+        _ => get(&item.children, rest),
+    }
+}
+
+pub fn get_mut<'a>(items: &'a mut [Item], path: &[usize]) -> Option<&'a mut Item> {
+    let (first, rest) = path.split_first()?;
+    let item = items.get_mut(*first)?;
+    match rest.is_empty() {
+        true => Some(item),
+        _ => get_mut(&mut item.children, rest),
+    }
+}
+
+/// The items below `parent`, the top level for an empty path.
+fn children_mut<'a>(items: &'a mut Vec<Item>, parent: &[usize]) -> Option<&'a mut Vec<Item>> {
+    match parent.is_empty() {
+        true => Some(items),
+        _ => get_mut(items, parent).map(|item| &mut item.children),
+    }
+}
+
+pub fn remove(items: &mut Vec<Item>, path: &[usize]) -> Option<Item> {
+    let (last, parent) = path.split_last()?;
+    let siblings = children_mut(items, parent)?;
+    (*last < siblings.len()).then(|| siblings.remove(*last))
+}
+
+pub fn insert(items: &mut Vec<Item>, path: &[usize], item: Item) -> Option<()> {
+    let (last, parent) = path.split_last()?;
+    let siblings = children_mut(items, parent)?;
+    (*last <= siblings.len()).then(|| siblings.insert(*last, item))
+}
+
+/// Makes an item the last subtask of its sibling `under`, returns its new
+/// path.
+pub fn indent(items: &mut Vec<Item>, path: &[usize], under: usize) -> Option<Vec<usize>> {
+    let (last, parent) = path.split_last()?;
+    let siblings = children_mut(items, parent)?;
+    if *last == under || *last >= siblings.len() || under >= siblings.len() {
+        return None;
+    }
+    let item = siblings.remove(*last);
+    let under = match under > *last {
+        true => under - 1,
+        _ => under,
+    };
+    let new_parent = &mut siblings[under];
+    new_parent.children.push(item);
+    new_parent.collapsed = false;
+    Some([parent, &[under, new_parent.children.len() - 1]].concat())
+}
+
+/// Moves a subtask up a level, right behind its former parent, returns its
+/// new path.
+pub fn outdent(items: &mut Vec<Item>, path: &[usize]) -> Option<Vec<usize>> {
+    let (_, parent) = path.split_last()?;
+    let (at, grandparent) = parent.split_last()?;
+    let item = remove(items, path)?;
+    children_mut(items, grandparent)?.insert(at + 1, item);
+    Some([grandparent, &[at + 1]].concat())
+}
+
+/// Paths of all items and their subtasks, parents before their children.
+pub fn paths(items: &[Item]) -> Vec<Vec<usize>> {
+    items
+        .iter()
+        .enumerate()
+        .flat_map(|(i, item)| {
+            std::iter::once(vec![i]).chain(paths(&item.children).into_iter().map(
+                move |mut path| {
+                    path.insert(0, i);
+                    path
+                },
+            ))
+        })
+        .collect()
+}
+
+/// All items and their subtasks, parents before their children.
+pub fn walk(items: &[Item]) -> Vec<&Item> {
+    items
+        .iter()
+        .flat_map(|item| std::iter::once(item).chain(walk(&item.children)))
+        .collect()
+}
+
+/// Items written before timestamps existed are plain entries.
+impl From<&str> for Item {
+    fn from(entry: &str) -> Self {
+        Self {
+            entry: entry.to_owned(),
+            created: None,
+            modified: None,
+            completed: None,
+            due: None,
+            scheduled: None,
+            priority: None,
+            recurrence: None,
+            completions: vec![],
+            notes: String::new(),
+            children: vec![],
+            collapsed: false,
+        }
+    }
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.entry)
+    }
+}
+
+impl AsRef<str> for Item {
+    fn as_ref(&self) -> &str {
+        &self.entry
+    }
+}
+
+impl Serialize for Item {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Item::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Item {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ItemVisitor;
+
+        impl<'de> Visitor<'de> for ItemVisitor {
+            type Value = Item;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "an entry or an item")
+            }
+
+            fn visit_str<E: de::Error>(self, entry: &str) -> Result<Item, E> {
+                Ok(Item::from(entry))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Item, A::Error> {
+                Item::deserialize(de::value::MapAccessDeserializer::new(map))
+            }
+        }
+
+        deserializer.deserialize_any(ItemVisitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn entries_and_items_deserialize() {
+        let items = serde_json::from_str::<Vec<Item>>(
+            r#"["1done", {"entry": "0open", "created": "2026-01-01T00:00:00Z"}]"#,
+        )
+        .unwrap();
+        assert!(items[0].is_done());
+        assert_eq!(items[0].created, None);
+        assert_eq!(items[1].text(), "open");
+        assert!(items[1].created.is_some());
+
+        let json = serde_json::to_string(&items[1]).unwrap();
+        assert_eq!(serde_json::from_str::<Item>(&json).unwrap(), items[1]);
+    }
+
+    #[test]
+    fn toggle_keeps_created() {
+        let then = Utc::now();
+        let mut item = Item::new("a", then);
+        item.toggle(then + chrono::Duration::days(1));
+        assert!(item.is_done());
+        assert_eq!(item.created, Some(then));
+        assert_eq!(item.completed, Some(then + chrono::Duration::days(1)));
+        item.toggle(then + chrono::Duration::days(2));
+        assert!(!item.is_done());
+        assert_eq!(item.completed, None);
+    }
+
+    #[test]
+    fn due_tokens() {
+        let at = Utc::now();
+        let today = at.with_timezone(&Local).date_naive();
+        let mut item = Item::new("pay rent due:today", at);
+        assert_eq!(item.text(), "pay rent");
+        assert_eq!(item.due, Some(today));
+        assert!(!item.is_overdue(today));
+        assert!(item.is_overdue(today + chrono::Duration::days(1)));
+
+        item.edit("pay the rent", at);
+        assert_eq!(item.due, Some(today));
+        item.edit("pay the rent due:", at);
+        assert_eq!(item.due, None);
+        item.edit("see due:later", at);
+        assert_eq!(item.text(), "see due:later");
+    }
+
+    #[test]
+    fn identity() {
+        let at = Utc::now();
+        let (a, b) = (Item::new("a", at), Item::new("b", at));
+        assert!(!a.is(&b));
+        let mut done = a.clone();
+        done.toggle(at + chrono::Duration::days(1));
+        assert!(done.is(&a));
+        assert!(!Item::new("a", at + chrono::Duration::days(1)).is(&a));
+        assert!(Item::from("0a").is(&Item::from("1a")));
+    }
+
+    #[test]
+    fn recurring_items_reopen() {
+        let at = Utc::now();
+        let today = at.with_timezone(&Local).date_naive();
+        let mut item = Item::new("water plants rec:3d due:today", at);
+        assert_eq!(item.text(), "water plants");
+        assert_eq!(item.recurrence, Some(Recurrence::Every(3)));
+        item.scheduled = item.due;
+
+        item.toggle(at);
+        assert!(!item.is_done());
+        assert_eq!(item.due, Some(today + chrono::Duration::days(3)));
+        assert_eq!(item.scheduled, item.due);
+        assert_eq!(item.completions, vec![at]);
+
+        item.edit("water plants rec:", at);
+        item.toggle(at);
+        assert!(item.is_done());
+    }
+
+    #[test]
+    fn priorities() {
+        let at = Utc::now();
+        let mut item = Item::new("a", at);
+        item.raise(at);
+        assert_eq!(item.priority, Some(Priority('C')));
+        item.raise(at);
+        item.raise(at);
+        item.raise(at);
+        assert_eq!(item.priority, Some(Priority('A')));
+        item.lower(at);
+        item.lower(at);
+        item.lower(at);
+        assert_eq!(item.priority, Some(Priority('D')));
+        item.priority = Some(Priority('Y'));
+        item.lower(at);
+        assert_eq!(item.priority, Some(Priority('Z')));
+        item.lower(at);
+        assert_eq!(item.priority, None);
+
+        assert!(serde_json::from_str::<Priority>(r#""a""#).is_err());
+        assert_eq!(serde_json::to_string(&Priority('B')).unwrap(), r#""B""#);
+    }
+
+    #[test]
+    fn subtasks() {
+        let mut items = vec![Item::from("0a"), Item::from("1b"), Item::from("0c")];
+        assert_eq!(indent(&mut items, &[1], 0), Some(vec![0, 0]));
+        assert_eq!(indent(&mut items, &[1], 0), Some(vec![0, 1]));
+        assert_eq!(items.len(), 1);
+        assert_eq!(get(&items, &[0, 1]).unwrap().entry, "0c");
+        assert_eq!(items[0].progress(), 0.5);
+        assert_eq!(indent(&mut items, &[0, 1], 1), None);
+
+        assert_eq!(indent(&mut items, &[0, 1], 0), Some(vec![0, 0, 0]));
+        get_mut(&mut items, &[0, 0, 0]).unwrap().toggle(Utc::now());
+        assert_eq!(items[0].progress(), 1.0);
+        assert_eq!(walk(&items).len(), 3);
+
+        assert_eq!(outdent(&mut items, &[0, 0, 0]), Some(vec![0, 1]));
+        assert_eq!(outdent(&mut items, &[0]), None);
+        assert_eq!(remove(&mut items, &[0, 0]).unwrap().entry, "1b");
+        assert_eq!(items[0].children.len(), 1);
+
+        let json = serde_json::to_string(&items).unwrap();
+        assert_eq!(serde_json::from_str::<Vec<Item>>(&json).unwrap(), items);
+    }
+
+    #[test]
+    fn documents() {
+        let at = Utc::now();
+        let mut item = Item::new("pay rent due:2026-11-01 rec:monthly", at);
+        assert_eq!(item.document(), "pay rent due:2026-11-01 rec:monthly\n");
+
+        item.revise("pay the rent rec:monthly\n\nby transfer\nref 42\n", at);
+        assert_eq!(item.text(), "pay the rent");
+        assert_eq!(item.due, None);
+        assert_eq!(item.recurrence, Some(Recurrence::Monthly));
+        assert_eq!(item.notes, "by transfer\nref 42");
+
+        let mut copy = item.clone();
+        copy.revise(&item.document(), at);
+        assert_eq!(copy, item);
+    }
+
+    #[test]
+    fn tags() {
+        let item = Item::new("call #Bob about # the #urgent thing", Utc::now());
+        assert_eq!(item.tags(), vec!["Bob", "urgent"]);
+        assert!(item.has_tag("bob"));
+        assert!(item.has_tag("#urgent"));
+        assert!(!item.has_tag("thing"));
+    }
+}
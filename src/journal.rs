@@ -0,0 +1,757 @@
+//! A store as a log of what was done to it, one JSON object per line:
+//!
+//! ```text
+//! {"at":"2026-10-18T09:30:00Z","op":"snapshot","store":{"data":{...},...}}
+//! {"at":"2026-10-18T09:31:12Z","op":"add","topic":"work","path":[3],"item":{...}}
+//! {"at":"2026-10-18T09:32:40Z","op":"toggle","topic":"work","path":[0,1],"item":{...}}
+//! ```
+//!
+//! Saving appends what changed since the store was loaded or last saved
+//! instead of rewriting it, so changes are saved as soon as they are made,
+//! and loading replays the log. Once the log has grown long it is compacted
+//! into a single snapshot.
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, prelude::*};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::backend::{self, Backend};
+use crate::item::{self, Item};
+use crate::memory::{self, Memory, MemoryError};
+
+type Result<T> = std::result::Result<T, MemoryError>;
+
+/// Lines after which the log is compacted on the next save.
+const COMPACT_AFTER: usize = 1000;
+
+/// What was done to a store. Items are addressed by topic and path, items of
+/// `toggle` and `edit` come without their subtasks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Op {
+    /// the whole store
+    Snapshot {
+        store: serde_json::Value,
+    },
+    AddTopic {
+        topic: String,
+        items: Vec<Item>,
+    },
+    DeleteTopic {
+        topic: String,
+    },
+    Rename {
+        from: String,
+        to: String,
+    },
+    /// items of a topic which changed too much to tell how, e.g. sorted
+    Topic {
+        topic: String,
+        items: Vec<Item>,
+    },
+    Add {
+        topic: String,
+        path: Vec<usize>,
+        item: Item,
+    },
+    Delete {
+        topic: String,
+        path: Vec<usize>,
+    },
+    Toggle {
+        topic: String,
+        path: Vec<usize>,
+        item: Item,
+    },
+    Edit {
+        topic: String,
+        path: Vec<usize>,
+        item: Item,
+    },
+    /// topic order, views, archive and settings
+    Meta {
+        meta: serde_json::Value,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    at: DateTime<Utc>,
+    #[serde(flatten)]
+    op: Op,
+}
+
+fn shallow(item: &Item) -> Item {
+    Item {
+        children: vec![],
+        ..item.clone()
+    }
+}
+
+/// What turns the items `before` into the ones `after`, unless they changed
+/// too much to tell.
+fn diff_items(
+    topic: &str,
+    parent: &[usize],
+    before: &[Item],
+    after: &[Item],
+    ops: &mut Vec<Op>,
+) -> Option<()> {
+    let path = |i: usize| [parent, &[i]].concat();
+    let same = before.iter().zip(after).take_while(|(b, a)| b == a).count();
+    match after.len() as isize - before.len() as isize {
+        1 if before[same..] == after[same + 1..] => ops.push(Op::Add {
+            topic: topic.to_owned(),
+            path: path(same),
+            item: after[same].clone(),
+        }),
+        -1 if before[same + 1..] == after[same..] => ops.push(Op::Delete {
+            topic: topic.to_owned(),
+            path: path(same),
+        }),
+        0 => {
+            for (i, (b, a)) in before.iter().zip(after).enumerate().skip(same) {
+                let (t, p, item) = (topic.to_owned(), path(i), shallow(a));
+                match shallow(b) == item {
+                    true => (),
+                    _ if b.text() == a.text()
+                        && (b.is_done() != a.is_done() || b.completions != a.completions) =>
+                    {
+                        ops.push(Op::Toggle {
+                            topic: t,
+                            path: p,
+                            item,
+                        })
+                    }
+                    _ => ops.push(Op::Edit {
+                        topic: t,
+                        path: p,
+                        item,
+                    }),
+                }
+                diff_items(topic, &path(i), &b.children, &a.children, ops)?;
+            }
+        }
+        _ => return None,
+    }
+    Some(())
+}
+
+/// What turns the store `before` into the one `after`.
+pub fn diff(before: &Memory<String, Item>, after: &Memory<String, Item>) -> Vec<Op> {
+    let mut ops = vec![];
+    let removed = before
+        .idxs
+        .iter()
+        .filter(|topic| !after.data.contains_key(*topic))
+        .collect::<Vec<_>>();
+    let added = after
+        .idxs
+        .iter()
+        .filter(|topic| !before.data.contains_key(*topic))
+        .collect::<Vec<_>>();
+    match (removed.as_slice(), added.as_slice()) {
+        ([from], [to]) if before.data.get(*from) == after.data.get(*to) => ops.push(Op::Rename {
+            from: from.to_string(),
+            to: to.to_string(),
+        }),
+        _ => {
+            removed.iter().for_each(|topic| {
+                ops.push(Op::DeleteTopic {
+                    topic: topic.to_string(),
+                })
+            });
+            added.iter().for_each(|topic| {
+                ops.push(Op::AddTopic {
+                    topic: topic.to_string(),
+                    items: after.data.get(*topic).cloned().unwrap_or_default(),
+                })
+            });
+        }
+    }
+
+    after.idxs.iter().for_each(|topic| {
+        let (Some(b), Some(a)) = (before.data.get(topic), after.data.get(topic)) else {
+            return;
+        };
+        if b == a {
+            return;
+        }
+        let mut changed = vec![];
+        match diff_items(topic, &[], b, a, &mut changed) {
+            Some(()) => ops.append(&mut changed),
+            None => ops.push(Op::Topic {
+                topic: topic.clone(),
+                items: a.clone(),
+            }),
+        }
+    });
+
+    if before.meta() != after.meta() {
+        if let Some(meta) = after.meta() {
+            ops.push(Op::Meta { meta });
+        }
+    }
+    ops
+}
+
+/// Does what `op` did to `memory` again.
+pub fn apply(memory: &mut Memory<String, Item>, op: Op) -> Option<()> {
+    fn topic(memory: &mut Memory<String, Item>, topic: String, items: Vec<Item>) {
+        if !memory.idxs.contains(&topic) {
+            memory.idxs.push(topic.clone());
+        }
+        memory.data.insert(topic, items);
+    }
+
+    match op {
+        Op::Snapshot { store } => *memory = memory::parse(&store.to_string())?,
+        Op::AddTopic { topic: t, items } | Op::Topic { topic: t, items } => topic(memory, t, items),
+        Op::DeleteTopic { topic } => {
+            memory.data.remove(&topic)?;
+            memory.idxs.retain(|t| *t != topic);
+        }
+        Op::Rename { from, to } => {
+            let items = memory.data.remove(&from)?;
+            memory.data.insert(to.clone(), items);
+            memory
+                .idxs
+                .iter_mut()
+                .filter(|t| **t == from)
+                .for_each(|t| *t = to.clone());
+        }
+        Op::Add { topic, path, item } => item::insert(memory.data.get_mut(&topic)?, &path, item)?,
+        Op::Delete { topic, path } => {
+            item::remove(memory.data.get_mut(&topic)?, &path)?;
+        }
+        Op::Toggle { topic, path, item } | Op::Edit { topic, path, item } => {
+            let old = item::get_mut(memory.data.get_mut(&topic)?, &path)?;
+            let children = std::mem::take(&mut old.children);
+            *old = Item { children, ..item };
+        }
+        Op::Meta { meta } => memory.set_meta(meta).ok()?,
+    }
+    Some(())
+}
+
+/// Where the item at `path` of `topic` in `base` is in `memory` now, found by
+/// what it is rather than where it was.
+fn find(
+    base: &Memory<String, Item>,
+    memory: &Memory<String, Item>,
+    topic: &str,
+    path: &[usize],
+) -> Option<Vec<usize>> {
+    let before = item::get(base.data.get(topic)?, path)?;
+    let items = memory.data.get(topic)?;
+    let is = |path: &Vec<usize>| item::get(items, path).is_some_and(|item| item.is(before));
+    // most likely still where it was, which also picks among items alike
+    let path = path.to_vec();
+    match is(&path) {
+        true => Some(path),
+        _ => item::paths(items).into_iter().find(is),
+    }
+}
+
+/// `op` made on `base`, addressed to the same items in `memory`. None if the
+/// items it was made on are gone, e.g. deleted or changed elsewhere.
+fn rebase(base: &Memory<String, Item>, memory: &Memory<String, Item>, op: Op) -> Option<Op> {
+    let find = |topic: &str, path: &[usize]| find(base, memory, topic, path);
+    Some(match op {
+        Op::Delete { topic, path } => Op::Delete {
+            path: find(&topic, &path)?,
+            topic,
+        },
+        Op::Toggle { topic, path, item } => Op::Toggle {
+            path: find(&topic, &path)?,
+            topic,
+            item,
+        },
+        Op::Edit { topic, path, item } => Op::Edit {
+            path: find(&topic, &path)?,
+            topic,
+            item,
+        },
+        // right behind the item it followed, or first below its parent
+        Op::Add { topic, path, item } => {
+            let (last, parent) = path.split_last()?;
+            let path = match (last.checked_sub(1), parent.is_empty()) {
+                (Some(before), _) => {
+                    find(&topic, &[parent, &[before]].concat()).and_then(|mut path| {
+                        *path.last_mut()? += 1;
+                        Some(path)
+                    })
+                }
+                (None, true) => Some(vec![0]),
+                (None, _) => find(&topic, parent).map(|path| [path, vec![0]].concat()),
+            };
+            // not to lose it, at the end of the topic
+            let path = path.unwrap_or(vec![memory.data.get(&topic)?.len()]);
+            Op::Add { topic, path, item }
+        }
+        Op::Topic { topic, items } => Op::Topic {
+            items: merge_items(
+                base.data.get(&topic).map_or(&[], Vec::as_slice),
+                items,
+                memory.data.get(&topic)?,
+            ),
+            topic,
+        },
+        Op::Meta { meta } => Op::Meta {
+            meta: merge_meta(&base.meta()?, &meta, &memory.meta()?),
+        },
+        op => op,
+    })
+}
+
+/// The items `ours` made of the ones in `base` as a whole, e.g. by sorting or
+/// indenting, made of `theirs` instead. Items stay where we put them and take
+/// what was changed there unless we changed them too, items deleted there are
+/// dropped and items added there kept.
+fn merge_items(base: &[Item], ours: Vec<Item>, theirs: &[Item]) -> Vec<Item> {
+    fn rebuild(base: &[&Item], theirs: &[&Item], ours: Vec<Item>) -> Vec<Item> {
+        ours.into_iter()
+            .flat_map(|mut item| {
+                let children = rebuild(base, theirs, std::mem::take(&mut item.children));
+                let Some(before) = base.iter().find(|before| before.is(&item)) else {
+                    return vec![Item { children, ..item }];
+                };
+                match theirs.iter().find(|after| after.is(before)) {
+                    // deleted there, what we put below it stays
+                    None => children,
+                    Some(after) if shallow(before) == shallow(&item) => vec![Item {
+                        children,
+                        ..shallow(after)
+                    }],
+                    Some(_) => vec![Item { children, ..item }],
+                }
+            })
+            .collect()
+    }
+
+    let before = item::walk(base);
+    let mut items = rebuild(&before, &item::walk(theirs), ours);
+    // added there, below the same parent if it is still around
+    item::paths(theirs)
+        .into_iter()
+        .zip(item::walk(theirs))
+        .filter(|(_, added)| !before.iter().any(|before| before.is(added)))
+        .for_each(|(path, added)| {
+            let parent = item::get(theirs, &path[..path.len() - 1]).and_then(|parent| {
+                item::paths(&items)
+                    .into_iter()
+                    .find(|path| item::get(&items, path).is_some_and(|item| item.is(parent)))
+            });
+            match parent.and_then(|parent| item::get_mut(&mut items, &parent)) {
+                Some(parent) => parent.children.push(shallow(added)),
+                None => items.push(shallow(added)),
+            }
+        });
+    items
+}
+
+/// `theirs` with what changed from `base` to `ours`, setting by setting and
+/// topic by topic. Lists changed on both sides, such as the archive, keep
+/// what either side added and lose what either side removed.
+fn merge_meta(
+    base: &serde_json::Value,
+    ours: &serde_json::Value,
+    theirs: &serde_json::Value,
+) -> serde_json::Value {
+    use serde_json::Value;
+
+    match (base, ours, theirs) {
+        _ if base == ours => theirs.clone(),
+        (Value::Object(base), Value::Object(ours), Value::Object(theirs)) => Value::Object(
+            base.keys()
+                .chain(ours.keys())
+                .chain(theirs.keys())
+                .collect::<std::collections::BTreeSet<_>>()
+                .into_iter()
+                .filter_map(|key| {
+                    let value = match (base.get(key), ours.get(key), theirs.get(key)) {
+                        (b, o, t) if b == o => t.cloned(),
+                        (Some(b), Some(o), Some(t)) => Some(merge_meta(b, o, t)),
+                        (_, o, _) => o.cloned(),
+                    };
+                    Some((key.clone(), value?))
+                })
+                .collect(),
+        ),
+        (Value::Array(base), Value::Array(ours), Value::Array(theirs)) => {
+            let kept = theirs
+                .iter()
+                .filter(|value| ours.contains(value) || !base.contains(value));
+            let added = ours
+                .iter()
+                .filter(|value| !base.contains(value) && !theirs.contains(value));
+            Value::Array(kept.chain(added).cloned().collect())
+        }
+        _ => ours.clone(),
+    }
+}
+
+/// `theirs` with what was done to `base` to get `ours` done again, as far as
+/// it still applies to it. Items are followed by what they are, so that
+/// changes made on either side do not land on other items, and settings are
+/// merged one by one; topics follow what is left of them.
+pub fn merge(
+    base: &Memory<String, Item>,
+    ours: &Memory<String, Item>,
+    theirs: Memory<String, Item>,
+) -> Memory<String, Item> {
+    let mut merged = theirs;
+    diff(base, ours).into_iter().for_each(|op| {
+        if let Some(op) = rebase(base, &merged, op) {
+            let _ = apply(&mut merged, op);
+        }
+    });
+    merged.reindex();
+    merged
+}
+
+pub struct Journal {
+    path: String,
+    /// The store as it is in the log.
+    saved: Option<Memory<String, Item>>,
+    /// Lines of the log.
+    lines: usize,
+    lock: Option<File>,
+}
+
+impl Journal {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            saved: None,
+            lines: 0,
+            lock: None,
+        }
+    }
+
+    /// Rewrites the log as a single snapshot of `memory`.
+    fn compact(&mut self, memory: &Memory<String, Item>) -> Result<()> {
+        let store = serde_json::from_str(&memory.serialize().ok_or(MemoryError::Unknown)?)?;
+        let entry = Entry {
+            at: Utc::now(),
+            op: Op::Snapshot { store },
+        };
+        // a log cut short by a crash is worse than a stale one
+        let tmp = format!("{}.tmp", self.path);
+        fs::write(&tmp, serde_json::to_string(&entry)? + "\n")?;
+        fs::rename(&tmp, &self.path)?;
+        self.lines = 1;
+        Ok(())
+    }
+}
+
+impl Backend<String, Item> for Journal {
+    fn load(&mut self) -> Result<Memory<String, Item>> {
+        let contents = match fs::read_to_string(&self.path) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            contents => contents?,
+        };
+        let lines = contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .collect::<Vec<_>>();
+        self.lines = lines.len();
+
+        let mut memory = Memory::new();
+        for (n, line) in lines.iter().enumerate() {
+            match serde_json::from_str::<Entry>(line) {
+                Ok(entry) => apply(&mut memory, entry.op).ok_or(MemoryError::Unknown)?,
+                // the last save was cut short, compact it away on the next one
+                Err(_) if n + 1 == lines.len() => self.lines = COMPACT_AFTER,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        self.saved = Some(memory.clone());
+        Ok(memory)
+    }
+
+    fn save(&mut self, memory: &Memory<String, Item>) -> Result<()> {
+        match &self.saved {
+            Some(saved) if self.lines > 0 && self.lines < COMPACT_AFTER => {
+                let at = Utc::now();
+                let lines = diff(saved, memory)
+                    .into_iter()
+                    .map(|op| serde_json::to_string(&Entry { at, op }).map(|line| line + "\n"))
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+                if !lines.is_empty() {
+                    OpenOptions::new()
+                        .append(true)
+                        .open(&self.path)?
+                        .write_all(lines.concat().as_bytes())?;
+                    self.lines += lines.len();
+                }
+            }
+            _ => self.compact(memory)?,
+        }
+        self.saved = Some(memory.clone());
+        Ok(())
+    }
+
+    fn path(&self) -> Option<&str> {
+        Some(&self.path)
+    }
+
+    fn saves_each_change(&self) -> bool {
+        true
+    }
+
+    fn lock(&mut self) -> Result<bool> {
+        backend::try_lock(&self.path, &mut self.lock)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ops(path: &str) -> Vec<String> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["op"].to_string())
+            .map(|op| op.trim_matches('"').to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn replayed() {
+        let path = std::env::temp_dir().join(format!("go22dos-{}.jsonl", std::process::id()));
+        let path = path.to_string_lossy().into_owned();
+        let _ = fs::remove_file(&path);
+        let now = Utc::now();
+
+        let mut journal = Journal::new(&path);
+        let mut memory = journal.load().unwrap();
+        memory.idxs = vec!["work".to_owned()];
+        memory.data.insert(
+            "work".to_owned(),
+            vec![Item::new("a", now), Item::new("b", now)],
+        );
+        journal.save(&memory).unwrap();
+
+        let items = memory.data.get_mut("work").unwrap();
+        items[0].children.push(Item::new("a1", now));
+        items[1].toggle(now);
+        journal.save(&memory).unwrap();
+        let items = memory.data.get_mut("work").unwrap();
+        items[0].edit("a!", now);
+        journal.save(&memory).unwrap();
+        memory.data.get_mut("work").unwrap().remove(1);
+        journal.save(&memory).unwrap();
+        let items = memory.data.remove("work").unwrap();
+        memory.data.insert("job".to_owned(), items);
+        memory.idxs = vec!["job".to_owned()];
+        journal.save(&memory).unwrap();
+        memory.idxs.push("home".to_owned());
+        memory.data.insert("home".to_owned(), vec![]);
+        journal.save(&memory).unwrap();
+        journal.save(&memory).unwrap();
+
+        assert_eq!(
+            ops(&path),
+            vec![
+                "snapshot",
+                "add",
+                "toggle",
+                "edit",
+                "delete",
+                "rename",
+                "meta",
+                "add_topic",
+                "meta"
+            ]
+        );
+        let loaded = Journal::new(&path).load().unwrap();
+        assert_eq!(loaded.idxs, memory.idxs);
+        assert_eq!(loaded.data, memory.data);
+        assert_eq!(loaded.meta(), memory.meta());
+
+        // a save cut short is dropped, the next one compacts
+        let mut f = OpenOptions::new().append(true).open(&path).unwrap();
+        f.write_all(b"{\"at\":").unwrap();
+        let mut journal = Journal::new(&path);
+        let mut memory = journal.load().unwrap();
+        memory
+            .data
+            .get_mut("home")
+            .unwrap()
+            .push(Item::new("c", now));
+        journal.save(&memory).unwrap();
+        assert_eq!(ops(&path), vec!["snapshot"]);
+        assert_eq!(Journal::new(&path).load().unwrap().data, memory.data);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn appended_as_they_happen() {
+        let path = std::env::temp_dir().join(format!("go22dos-{}-each.jsonl", std::process::id()));
+        let path = path.to_string_lossy().into_owned();
+        let _ = fs::remove_file(&path);
+
+        let mut journal = Journal::new(&path);
+        assert!(journal.saves_each_change());
+        let mut memory = journal.load().unwrap();
+        memory.idxs = vec!["work".to_owned()];
+        memory.data.insert("work".to_owned(), vec![]);
+        journal.save(&memory).unwrap();
+        ["a", "b"].iter().for_each(|text| {
+            std::thread::sleep(std::time::Duration::from_millis(2));
+            let items = memory.data.get_mut("work").unwrap();
+            items.push(Item::new(text, Utc::now()));
+            journal.save(&memory).unwrap();
+        });
+
+        let entries = fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<Entry>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(ops(&path), vec!["snapshot", "add", "add"]);
+        assert!(entries[1].at < entries[2].at);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn merged() {
+        let now = Utc::now();
+        let mut base = Memory::new();
+        base.idxs = vec!["work".to_owned()];
+        base.data.insert(
+            "work".to_owned(),
+            vec![Item::new("a", now), Item::new("b", now)],
+        );
+
+        let mut ours = base.clone();
+        ours.data.get_mut("work").unwrap()[0].toggle(now);
+        ours.idxs.push("home".to_owned());
+        ours.data.insert("home".to_owned(), vec![]);
+        let mut theirs = base.clone();
+        let items = theirs.data.get_mut("work").unwrap();
+        items[1].edit("b!", now);
+        items.push(Item::new("c", now));
+
+        let merged = merge(&base, &ours, theirs);
+        assert_eq!(merged.idxs, vec!["work", "home"]);
+        let items = &merged.data["work"];
+        assert_eq!(
+            items.iter().map(Item::text).collect::<Vec<_>>(),
+            vec!["a", "b!", "c"]
+        );
+        assert!(items[0].is_done());
+    }
+
+    #[test]
+    fn merged_settings_and_rearranged_topics() {
+        let now = Utc::now();
+        let mut base = Memory::new();
+        base.idxs = vec!["work".to_owned(), "old".to_owned()];
+        base.data.insert(
+            "work".to_owned(),
+            vec![
+                Item::new("a", now),
+                Item::new("b", now),
+                Item::new("c", now),
+            ],
+        );
+        base.data
+            .insert("old".to_owned(), vec![Item::new("x", now)]);
+
+        // archived there, along with a setting changed
+        let mut theirs = base.clone();
+        let items = theirs.data.remove("old").unwrap();
+        theirs.idxs.retain(|topic| topic != "old");
+        theirs.archive.push(memory::Archived {
+            topic: "old".to_owned(),
+            items,
+            created: None,
+            archived: now,
+        });
+        theirs.auto_archive = Some(5);
+        let items = theirs.data.get_mut("work").unwrap();
+        items[2].toggle(now);
+        items.push(Item::new("d", now));
+
+        // a topic added and b indented below a here
+        let mut ours = base.clone();
+        ours.idxs.push("home".to_owned());
+        ours.data.insert("home".to_owned(), vec![]);
+        let items = ours.data.get_mut("work").unwrap();
+        item::indent(items, &[1], 0);
+
+        let merged = merge(&base, &ours, theirs);
+        assert_eq!(merged.idxs, vec!["work", "home"]);
+        assert_eq!(merged.archive.len(), 1);
+        assert_eq!(merged.archive[0].topic, "old");
+        assert_eq!(merged.auto_archive, Some(5));
+        let texts = item::walk(&merged.data["work"])
+            .iter()
+            .map(|item| format!("{}{}", item.is_done() as u8, item.text()))
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["0a", "0b", "1c", "0d"]);
+        assert_eq!(merged.data["work"][0].children[0].text(), "b");
+    }
+
+    #[test]
+    fn merged_where_items_moved() {
+        let now = Utc::now();
+        let texts = |memory: &Memory<String, Item>| {
+            item::walk(&memory.data["work"])
+                .iter()
+                .map(|item| format!("{}{}", item.is_done() as u8, item.text()))
+                .collect::<Vec<_>>()
+        };
+        let mut base = Memory::new();
+        base.idxs = vec!["work".to_owned()];
+        // imported at once
+        base.data.insert(
+            "work".to_owned(),
+            vec![
+                Item::new("a", now),
+                Item::new("b", now),
+                Item::new("c", now),
+            ],
+        );
+        let mut theirs = base.clone();
+        let items = theirs.data.get_mut("work").unwrap();
+        items.insert(0, Item::new("x", now));
+        items.remove(3);
+
+        let mut ours = base.clone();
+        ours.data.get_mut("work").unwrap()[0].toggle(now);
+        let merged = merge(&base, &ours, theirs.clone());
+        assert_eq!(texts(&merged), vec!["0x", "1a", "0b"]);
+
+        let mut ours = base.clone();
+        let items = ours.data.get_mut("work").unwrap();
+        items.remove(1);
+        let merged = merge(&base, &ours, theirs.clone());
+        assert_eq!(texts(&merged), vec!["0x", "0a"]);
+
+        let mut ours = base.clone();
+        ours.data
+            .get_mut("work")
+            .unwrap()
+            .insert(1, Item::new("a2", now));
+        let merged = merge(&base, &ours, theirs.clone());
+        assert_eq!(texts(&merged), vec!["0x", "0a", "0a2", "0b"]);
+
+        let mut ours = base.clone();
+        ours.data.get_mut("work").unwrap()[0]
+            .children
+            .push(Item::new("a1", now));
+        let merged = merge(&base, &ours, theirs.clone());
+        assert_eq!(texts(&merged), vec!["0x", "0a", "0a1", "0b"]);
+
+        // gone there, so it stays gone
+        let mut ours = base.clone();
+        ours.data.get_mut("work").unwrap()[2].toggle(now);
+        let merged = merge(&base, &ours, theirs);
+        assert_eq!(texts(&merged), vec!["0x", "0a", "0b"]);
+    }
+}
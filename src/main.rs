@@ -1,20 +1,39 @@
+mod backend;
+mod cli;
 mod config;
+mod date;
+mod editor;
+mod exchange;
+mod ical;
+mod item;
+mod journal;
+mod markdown;
 mod memory;
+mod org;
+mod report;
+mod scan;
+mod sqlite;
+mod table;
+mod taskwarrior;
+mod todotxt;
 mod ui;
+mod watch;
 
+use chrono::Utc;
+use item::Item;
 use ll::has_colors;
 use ncurses::*;
 use std::fs::{metadata, File};
 use std::io::prelude::*;
 use std::sync::{Arc, Mutex};
 use thiserror::Error;
-use whoami;
 
 #[derive(Debug, Error)]
 pub enum Error {
     PoisenedMutexError,
     MemoryError(#[from] memory::MemoryError),
     UserInterfaceError(#[from] ui::UserInterfaceError),
+    ExchangeError(#[from] exchange::ExchangeError),
     IoError(#[from] std::io::Error),
     Unknown,
 }
@@ -30,10 +49,8 @@ fn startup() {
     initscr();
     noecho();
     curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
-    if unsafe { has_colors() == 1 } {
-        if start_color() == OK {
-            config::init_pairs();
-        }
+    if unsafe { has_colors() == 1 } && start_color() == OK {
+        config::init_pairs();
     }
 }
 
@@ -42,9 +59,10 @@ fn start_screen() {
     let w = stdscr();
 
     getmaxyx(w, &mut y, &mut x);
+    let k = config::START_SCREEN.len() as i32;
     config::START_SCREEN.iter().enumerate().for_each(|(i, s)| {
         let j = s.len() as i32;
-        mvprintw((y / 2) + i as i32, (x / 2) - (j / 2), s);
+        mvprintw((y / 2) - (k / 2) + i as i32, (x / 2) - (j / 2), s);
     })
 }
 
@@ -53,7 +71,7 @@ fn display_command(c: i32, i: i32) {
 
     let y = getmaxy(w);
 
-    mvprintw(y - 1 as i32, 0 + i, &format!("{}", c as u8 as char));
+    mvprintw(y - 1, i, &format!("{}", c as u8 as char));
 }
 
 fn display(s: &str) {
@@ -63,21 +81,240 @@ fn display(s: &str) {
     let x = getmaxx(w);
 
     let j = s.len() as i32;
-    mvprintw(y - 1 as i32, (x / 2) - (j / 2) as i32, &format!("{s}"));
+    mv(y - 1, 0);
+    clrtoeol();
+    mvprintw(y - 1, (x / 2) - (j / 2), s);
+}
+
+/// Reads a line of text typed in at the center of the screen, ESC ends it.
+fn input() -> String {
+    let (mut x, mut y) = (0, 0);
+    getmaxyx(stdscr(), &mut y, &mut x);
+    mvprintw(y / 2, x / 2, "");
+
+    curs_set(CURSOR_VISIBILITY::CURSOR_VISIBLE);
+
+    let mut t = "".to_owned();
+    let mut n = 0;
+
+    loop {
+        let c = getch();
+
+        match c {
+            config::ESC => break,
+            config::REMOVE => {
+                t.pop();
+                n -= 1;
+                mvprintw(y / 2, (x / 2) + n, " ");
+                mvprintw(y / 2, x / 2, &t);
+            }
+            _ => {
+                t = format!("{}{}", t, c as u8 as char);
+                n += 1;
+                mvprintw(y / 2, x / 2, &t);
+            }
+        }
+    }
+
+    attron(COLOR_PAIR(config::HIGHLIGHT_PAIR));
+    addstr(&t);
+    attroff(COLOR_PAIR(config::HIGHLIGHT_PAIR));
+
+    curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
+    t
+}
+
+/// Edits a line of text at the center of the screen, ESC ends it.
+fn edit_line(mut t: String) -> String {
+    let (mut x, mut y) = (0, 0);
+    getmaxyx(stdscr(), &mut y, &mut x);
+    let mut n = t.len().saturating_sub(1);
+    let mut curat = n;
+    mvprintw(y / 2, (x / 2) + n as i32, &t);
+
+    curs_set(CURSOR_VISIBILITY::CURSOR_VISIBLE);
+
+    loop {
+        let c = getch();
+
+        match c {
+            17 => match getch() {
+                // ctrl
+                103 => {
+                    // g
+                    curat -= 1;
+                    mvcur(
+                        y / 2,
+                        (x / 2) + (curat + 1) as i32,
+                        y / 2,
+                        (x / 2) + curat as i32,
+                    );
+                }
+                104 => {
+                    // h
+                    if curat < (t.len() - 1) {
+                        curat += 1;
+                        mvcur(
+                            y / 2,
+                            (x / 2) + (curat - 1) as i32,
+                            y / 2,
+                            (x / 2) + curat as i32,
+                        );
+                    }
+                }
+                _ => continue,
+            },
+            config::REMOVE => {
+                //mvprintw(
+                //    y / 2,
+                //    (x / 2) + n as i32,
+                //    &t.chars()
+                //        .skip(curat)
+                //        .next()
+                //        .unwrap()
+                //        .to_string(),
+                //);
+                //mvprintw(
+                //    y / 2,
+                //    (x / 2) + n as i32,
+                //    &std::iter::repeat(" ")
+                //        //.take(n + n - 1)
+                //        .take(n+1)
+                //        .collect::<String>(),
+                //);
+                //mvcur(
+                //    y / 2,
+                //    (x / 2) + (curat + 1) as i32,
+                //    y / 2,
+                //    (x / 2) + curat as i32,
+                //);
+                //mvprintw(y / 2, (x / 2) + n as i32, &t);
+                for (i, c_) in t.chars().enumerate() {
+                    match i == curat {
+                        false => mvprintw(y / 2, (x / 2) + i as i32, c_.to_string().as_str()),
+                        true => mvprintw(y / 2, (x / 2) + i as i32, ""),
+                    };
+                }
+                mvprintw(y / 2, (x / 2) + n as i32, &" ".repeat(n + 1));
+
+                t.remove(curat);
+                curat -= 1;
+                n -= 1;
+            }
+            config::ESC => break,
+            _ => {
+                t = format!("{}{}", t, c as u8 as char);
+                n += 1;
+                mvprintw(y / 2, x / 2, &t);
+            }
+        }
+    }
+
+    attron(COLOR_PAIR(config::HIGHLIGHT_PAIR));
+    addstr(&t);
+    attroff(COLOR_PAIR(config::HIGHLIGHT_PAIR));
+
+    curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
+    t
+}
+
+/// Edits notes on the whole screen, ESC ends it.
+fn edit_notes(notes: &str) -> String {
+    let (mut x, mut y) = (0, 0);
+    getmaxyx(stdscr(), &mut y, &mut x);
+    let mut editor = editor::Editor::new(notes, (y - 1) as usize, x as usize);
+
+    clear();
+    keypad(stdscr(), true);
+    curs_set(CURSOR_VISIBILITY::CURSOR_VISIBLE);
+    loop {
+        display("notes");
+        editor.draw();
+        if !editor.process(getch()) {
+            break;
+        }
+    }
+    curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
+    keypad(stdscr(), false);
+    clear();
+
+    editor.text()
+}
+
+/// Creates a file only we can read, under a name nobody can guess, so that
+/// nothing planted in the temp dir is followed or read.
+fn temp_file(ext: &str) -> Result<(std::path::PathBuf, File)> {
+    use std::hash::{BuildHasher, RandomState};
+    use std::os::unix::fs::OpenOptionsExt;
+
+    loop {
+        let suffix = RandomState::new().hash_one(std::time::SystemTime::now());
+        let path = std::env::temp_dir().join(format!("go22dos-{suffix:016x}.{ext}"));
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&path)
+        {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Suspends the screen to edit `text` in `$VISUAL` or `$EDITOR`, in a file
+/// with the extension `ext`.
+fn external_edit(text: &str, ext: &str) -> Result<String> {
+    let (path, mut file) = temp_file(ext)?;
+    if let Err(e) = file.write_all(text.as_bytes()) {
+        let _ = std::fs::remove_file(&path);
+        return Err(e.into());
+    }
+    drop(file);
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_owned());
+    let mut words = editor.split_whitespace();
+
+    def_prog_mode();
+    endwin();
+    let status = std::process::Command::new(words.next().unwrap_or("vi"))
+        .args(words)
+        .arg(&path)
+        .status();
+    reset_prog_mode();
+    refresh();
+
+    let text = match status {
+        Ok(status) if status.success() => std::fs::read_to_string(&path).map_err(Error::IoError),
+        Ok(_) => Err(Error::Unknown),
+        Err(e) => Err(Error::IoError(e)),
+    };
+    let _ = std::fs::remove_file(&path);
+    text
 }
 
-fn init(file: &str) -> Result<Arc<Mutex<memory::Memory<String, String>>>> {
-    memory::deserialize::<String, String>(file)
+fn init(
+    backend: &mut dyn backend::Backend<String, Item>,
+) -> Result<Arc<Mutex<memory::Memory<String, Item>>>> {
+    backend
+        .load()
         .map(|mem| Arc::new(Mutex::new(mem)))
-        .ok_or(Error::Unknown)
+        .map_err(|_| Error::Unknown)
 }
 
-fn save<K, V>(a2do: &impl memory::MemoryManagement<K, V>, file: &str) -> Result<()>
+fn save<K, V>(
+    a2do: &impl memory::MemoryManagement<K, V>,
+    backend: &mut dyn backend::Backend<K, V>,
+) -> Result<()>
 where
     K: memory::Serializable,
     V: memory::Serializable,
 {
-    a2do.save(file.to_owned()).map_err(Error::MemoryError)
+    a2do.save(backend).map_err(Error::MemoryError)
 }
 
 fn topics<K, V>(a2do: &impl ui::UserInterface<K, V>, on: usize) -> Result<()>
@@ -98,6 +335,15 @@ where
         .map_err(Error::UserInterfaceError)
 }
 
+fn tagged_items<K, V>(a2do: &impl ui::UserInterface<K, V>, tag: &str, on: usize) -> Result<()>
+where
+    K: memory::Serializable,
+    V: memory::Serializable,
+{
+    a2do.display_tagged(tag, on)
+        .map_err(Error::UserInterfaceError)
+}
+
 fn add_topic<K, V>(
     a2do: &mut impl memory::MemoryManagement<K, V>,
     topic: impl Into<K>,
@@ -120,7 +366,7 @@ where
 fn delete_item<K, V>(
     a2do: &mut impl memory::MemoryManagement<K, V>,
     on: usize,
-    on_item: usize,
+    on_item: &[usize],
 ) -> Result<V>
 where
     K: memory::Serializable,
@@ -132,7 +378,7 @@ where
 fn access_item<K, V>(
     a2do: &mut impl memory::MemoryManagement<K, V>,
     on: usize,
-    on_item: usize,
+    on_item: &[usize],
 ) -> Result<V>
 where
     K: memory::Serializable,
@@ -141,6 +387,108 @@ where
     a2do.access_item(on, on_item).map_err(Error::MemoryError)
 }
 
+fn update_item<K, V>(
+    a2do: &mut impl memory::MemoryManagement<K, V>,
+    on: usize,
+    on_item: &[usize],
+    update: impl FnOnce(&mut V),
+) -> Result<()>
+where
+    K: memory::Serializable,
+    V: memory::Serializable,
+{
+    a2do.update_item(on, on_item, update)
+        .map_err(Error::MemoryError)
+}
+
+fn access_topic<K, V>(a2do: &mut impl memory::MemoryManagement<K, V>, on: usize) -> Result<Vec<V>>
+where
+    K: memory::Serializable,
+    V: memory::Serializable,
+{
+    a2do.access_topic(on).map_err(Error::MemoryError)
+}
+
+fn update_topic<K, V>(
+    a2do: &mut impl memory::MemoryManagement<K, V>,
+    on: usize,
+    update: impl FnOnce(&mut Vec<V>),
+) -> Result<()>
+where
+    K: memory::Serializable,
+    V: memory::Serializable,
+{
+    a2do.update_topic(on, update).map_err(Error::MemoryError)
+}
+
+fn indent_item<K, V>(
+    a2do: &mut impl memory::MemoryManagement<K, V>,
+    on: usize,
+    on_item: &[usize],
+    under: usize,
+) -> Result<Vec<usize>>
+where
+    K: memory::Serializable,
+    V: memory::Serializable,
+{
+    a2do.indent_item(on, on_item, under)
+        .map_err(Error::MemoryError)
+}
+
+fn outdent_item<K, V>(
+    a2do: &mut impl memory::MemoryManagement<K, V>,
+    on: usize,
+    on_item: &[usize],
+) -> Result<Vec<usize>>
+where
+    K: memory::Serializable,
+    V: memory::Serializable,
+{
+    a2do.outdent_item(on, on_item).map_err(Error::MemoryError)
+}
+
+fn rows<K>(a2do: &impl memory::MemoryManagement<K, Item>, on: usize) -> Vec<Vec<usize>>
+where
+    K: memory::Serializable,
+{
+    a2do.lock_unwrap().view(on)
+}
+
+fn tagged_rows<K>(
+    a2do: &impl memory::MemoryManagement<K, Item>,
+    tag: &str,
+) -> Vec<(usize, Vec<usize>)>
+where
+    K: memory::Serializable,
+{
+    a2do.lock_unwrap().tagged(tag)
+}
+
+fn topic_rows<K>(a2do: &impl memory::MemoryManagement<K, Item>) -> Vec<usize>
+where
+    K: memory::Serializable,
+{
+    a2do.lock_unwrap().topic_view()
+}
+
+/// Position of a topic or an item within the rows of a view.
+fn position<T: PartialEq>(view: &[T], on: &T) -> usize {
+    view.iter().position(|i| i == on).unwrap_or(0)
+}
+
+/// The sibling displayed right above an item, which becomes its parent when
+/// the item is indented.
+fn sibling_above(view: &[Vec<usize>], on_item: usize) -> Option<usize> {
+    let at = view.get(on_item)?;
+    view[..on_item]
+        .iter()
+        .rev()
+        .find(|row| row.len() <= at.len())
+        .filter(|row| row.len() == at.len())
+        .and_then(|row| row.last())
+        .copied()
+}
+
 fn delete_topic<K, V>(a2do: &mut impl memory::MemoryManagement<K, V>, on: usize) -> Result<()>
 where
     K: memory::Serializable,
@@ -149,15 +497,206 @@ where
     a2do.delete_topic(on).map_err(Error::MemoryError)
 }
 
+fn hide_done<K, V>(a2do: &mut impl memory::MemoryManagement<K, V>, on: usize) -> Result<bool>
+where
+    K: memory::Serializable,
+    V: memory::Serializable,
+{
+    a2do.hide_done(on).map_err(Error::MemoryError)
+}
+
+fn sort_items<K, V>(
+    a2do: &mut impl memory::MemoryManagement<K, V>,
+    on: usize,
+) -> Result<memory::Sort>
+where
+    K: memory::Serializable,
+    V: memory::Serializable,
+{
+    a2do.sort_items(on).map_err(Error::MemoryError)
+}
+
+fn sort_topics<K, V>(a2do: &mut impl memory::MemoryManagement<K, V>) -> memory::TopicSort
+where
+    K: memory::Serializable,
+    V: memory::Serializable,
+{
+    a2do.sort_topics()
+}
+
+fn remember_views<K, V>(a2do: &mut impl memory::MemoryManagement<K, V>) -> bool
+where
+    K: memory::Serializable,
+    V: memory::Serializable,
+{
+    a2do.remember_views()
+}
+
+fn archive<K, V>(a2do: &impl ui::UserInterface<K, V>, on: usize) -> Result<()>
+where
+    K: memory::Serializable,
+    V: memory::Serializable,
+{
+    a2do.display_archive(on).map_err(Error::UserInterfaceError)
+}
+
+fn archive_topic<K, V>(a2do: &mut impl memory::MemoryManagement<K, V>, on: usize) -> Result<()>
+where
+    K: memory::Serializable,
+    V: memory::Serializable,
+{
+    a2do.archive_topic(on).map_err(Error::MemoryError)
+}
+
+fn restore_topic<K, V>(
+    a2do: &mut impl memory::MemoryManagement<K, V>,
+    on_archived: usize,
+) -> Result<()>
+where
+    K: memory::Serializable,
+    V: memory::Serializable,
+{
+    a2do.restore_topic(on_archived).map_err(Error::MemoryError)
+}
+
+fn delete_archived<K, V>(
+    a2do: &mut impl memory::MemoryManagement<K, V>,
+    on_archived: usize,
+) -> Result<()>
+where
+    K: memory::Serializable,
+    V: memory::Serializable,
+{
+    a2do.delete_archived(on_archived)
+        .map_err(Error::MemoryError)
+}
+
+fn set_auto_archive<K, V>(a2do: &mut impl memory::MemoryManagement<K, V>, days: Option<u32>)
+where
+    K: memory::Serializable,
+    V: memory::Serializable,
+{
+    a2do.set_auto_archive(days)
+}
+
+fn auto_archive<K, V>(a2do: &mut impl memory::MemoryManagement<K, V>) -> usize
+where
+    K: memory::Serializable,
+    V: memory::Serializable,
+{
+    a2do.auto_archive()
+}
+
+fn import<K, V>(a2do: &mut impl memory::MemoryManagement<K, V>, topics: Vec<(K, Vec<V>)>) -> usize
+where
+    K: memory::Serializable,
+    V: memory::Serializable,
+{
+    a2do.import(topics)
+}
+
+fn export<K, V>(
+    a2do: &mut impl memory::MemoryManagement<K, V>,
+    on: Option<usize>,
+) -> Result<Vec<(K, Vec<V>)>>
+where
+    K: memory::Serializable,
+    V: memory::Serializable,
+{
+    a2do.export(on).map_err(Error::MemoryError)
+}
+
+/// Waits for a key, or for the store to change on disk, which gives `None`.
+fn wait(watch: &mut Option<watch::Watch>) -> Option<i32> {
+    let Some(watch) = watch else {
+        return Some(getch());
+    };
+    timeout(config::WATCH_INTERVAL);
+    let c = loop {
+        match getch() {
+            ERR if watch.changed() => break None,
+            ERR => (),
+            c => break Some(c),
+        }
+    };
+    timeout(-1);
+    c
+}
+
+/// Takes in the store as it is on disk now, keeping what was done here since
+/// `base` was loaded. False if it could not be loaded, e.g. while it is still
+/// being written.
+fn reload(
+    a2do: &mut Arc<Mutex<memory::Memory<String, Item>>>,
+    base: &mut memory::Memory<String, Item>,
+    backend: &mut dyn backend::Backend<String, Item>,
+) -> Result<bool> {
+    let Ok(theirs) = backend.load() else {
+        return Ok(false);
+    };
+    let mut ctx = a2do.lock().map_err(|_| Error::PoisenedMutexError)?;
+    *ctx = journal::merge(base, &ctx, theirs.clone());
+    *base = theirs;
+    Ok(true)
+}
+
+/// Saves what was done here since `base` right away, for stores which are
+/// saved after every change.
+fn save_change(
+    a2do: &Arc<Mutex<memory::Memory<String, Item>>>,
+    base: &mut memory::Memory<String, Item>,
+    backend: &mut dyn backend::Backend<String, Item>,
+) -> Result<()> {
+    if !backend.saves_each_change() {
+        return Ok(());
+    }
+    let ctx = a2do.lock().map_err(|_| Error::PoisenedMutexError)?;
+    if !journal::diff(base, &ctx).is_empty() {
+        backend.save(&ctx).map_err(Error::MemoryError)?;
+        *base = ctx.clone();
+    }
+    Ok(())
+}
+
+/// The topic an index of `idxs` is on, to find it again after a reload.
+fn topic_of(a2do: &Arc<Mutex<memory::Memory<String, Item>>>, on: usize) -> Option<String> {
+    a2do.lock().ok()?.idxs.get(on).cloned()
+}
+
+fn topic_on(a2do: &Arc<Mutex<memory::Memory<String, Item>>>, topic: &str) -> Option<usize> {
+    a2do.lock().ok()?.idxs.iter().position(|t| t == topic)
+}
+
+/// Whether `c` would change a store which is open read-only, where nothing
+/// changed would be saved.
+fn refused(c: i32, read_only: bool) -> bool {
+    let refused = read_only && config::CHANGES.contains(&c);
+    if refused {
+        flash();
+    }
+    refused
+}
+
+/// Leaves a store alone which another go22dos has open.
+fn in_use(location: &str) -> ! {
+    eprintln!("{location} is open in another go22dos, try again once it is closed");
+    std::process::exit(1);
+}
+
 fn main() -> Result<()> {
-    startup();
+    let args = match cli::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(usage) => {
+            eprintln!("{usage}");
+            std::process::exit(2);
+        }
+    };
 
-    let (user, device) = (whoami::username(), whoami::devicename());
-    let file = match std::env::args().skip(1).next() {
+    let location = match args.store.or(std::env::var(config::STORE_VAR).ok()) {
         Some(x) => x,
         _ => {
-            let s = "_.go22dos".to_owned();
-            if !metadata(&s).is_ok() {
+            let s = config::DATA.to_owned();
+            if metadata(&s).is_err() {
                 File::create(s.clone())
                     .and_then(|mut f| f.write_all(b"{}"))
                     .map_err(|_| Error::Unknown)?;
@@ -165,16 +704,133 @@ fn main() -> Result<()> {
             s
         }
     };
-    let mut a2do = init(&file)?;
+    let mut store = backend::open(&location)?;
+    // another go22dos saving last would silently undo what is done here
+    let read_only = !store.lock()?;
+    let mut a2do = init(store.as_mut())?;
+
+    match args.command {
+        cli::Command::Tui => (),
+        cli::Command::Import { file: path } => {
+            if read_only {
+                in_use(&location);
+            }
+            let (topics, skipped) = exchange::import(&path, Utc::now())?;
+            let n = import(&mut a2do, topics);
+            save(&a2do, store.as_mut())?;
+            println!("imported {n} todos from {path}");
+            skipped.iter().for_each(|row| eprintln!("skipped {row}"));
+            return Ok(());
+        }
+        cli::Command::Export {
+            file: path,
+            topics: names,
+            split,
+        } => {
+            let mut topics = export(&mut a2do, None)?;
+            if !names.is_empty() {
+                topics.retain(|(topic, _)| names.contains(topic));
+                names
+                    .iter()
+                    .filter(|name| !topics.iter().any(|(topic, _)| topic == *name))
+                    .for_each(|name| eprintln!("no topic {name}"));
+            }
+            match split {
+                true => {
+                    for topic in topics.chunks(1) {
+                        let path = exchange::split(&path, &topic[0].0);
+                        exchange::export(&path, topic)?;
+                        println!("exported {} to {path}", topic[0].0);
+                    }
+                }
+                _ => {
+                    exchange::export(&path, &topics)?;
+                    println!("exported {} topics to {path}", topics.len());
+                }
+            }
+            return Ok(());
+        }
+        cli::Command::Scan { dir, topic } => {
+            if read_only {
+                in_use(&location);
+            }
+            let topic = topic.unwrap_or(config::SCAN_TOPIC.to_owned());
+            let comments = scan::walk(
+                std::path::Path::new(&dir),
+                store.path().map(std::path::Path::new),
+            )
+            .map_err(Error::IoError)?;
+            let on = match export(&mut a2do, None)?
+                .iter()
+                .position(|(t, _)| *t == topic)
+            {
+                Some(on) => on,
+                None => {
+                    add_topic(&mut a2do, topic.clone())?;
+                    topic_rows(&a2do).len() - 1
+                }
+            };
+            let mut synced = (0, 0);
+            update_topic(&mut a2do, on, |items| {
+                synced = scan::sync(items, &comments, Utc::now())
+            })?;
+            save(&a2do, store.as_mut())?;
+            println!(
+                "found {} todo comments, {} new and {} gone, in {topic}",
+                comments.len(),
+                synced.0,
+                synced.1
+            );
+            return Ok(());
+        }
+        cli::Command::Migrate { to } => {
+            let mut target = backend::open(&to)?;
+            if !target.lock()? {
+                in_use(&to);
+            }
+            // a store which cannot be loaded yet is empty
+            if target
+                .load()
+                .is_ok_and(|mem| !mem.idxs.is_empty() || !mem.archive.is_empty())
+            {
+                eprintln!("{to} is not empty");
+                std::process::exit(1);
+            }
+            save(&a2do, target.as_mut())?;
+            println!(
+                "migrated {} topics from {location} to {to}",
+                topic_rows(&a2do).len()
+            );
+            return Ok(());
+        }
+        cli::Command::Report { html: path } => {
+            let topics = export(&mut a2do, None)?;
+            std::fs::write(&path, report::html(&topics, Utc::now())).map_err(Error::IoError)?;
+            println!("reported {} topics to {path}", topics.len());
+            return Ok(());
+        }
+    }
+
+    startup();
+
+    let mut status = format!("{} @ {}", whoami::username(), whoami::devicename());
+    if read_only {
+        status.push_str(&format!(" | read-only, {location} is open elsewhere"));
+    }
+    // what is on disk, for changes made elsewhere to be merged with
+    let mut base = a2do.lock().map_err(|_| Error::PoisenedMutexError)?.clone();
+    let mut watch = store.watch();
+    if !read_only {
+        auto_archive(&mut a2do);
+    }
     let mut on = 0;
 
     loop {
         start_screen();
-        display(&format!("{} @ {}", user, device));
+        display(&status);
 
-        let ctx = a2do.lock().unwrap();
-        let mut ubt = ctx.idxs.len();
-        drop(ctx);
+        let mut tview = topic_rows(&a2do);
+        let mut ubt = tview.len();
 
         let c = getch();
 
@@ -188,268 +844,541 @@ fn main() -> Result<()> {
                 topics(&a2do, on)?;
 
                 loop {
-                    let mut c = getch();
+                    if !read_only {
+                        save_change(&a2do, &mut base, store.as_mut())?;
+                    }
+                    let Some(c) = wait(&mut watch) else {
+                        let topic = topic_of(&a2do, on);
+                        if reload(&mut a2do, &mut base, store.as_mut())? {
+                            tview = topic_rows(&a2do);
+                            ubt = tview.len();
+                            on = topic
+                                .and_then(|topic| topic_on(&a2do, &topic))
+                                .or(tview.first().copied())
+                                .unwrap_or(0);
+                            clear();
+
+                            display("topics");
+                            topics(&a2do, on)?;
+                        }
+                        continue;
+                    };
 
                     display_command(c, 1);
+                    if refused(c, read_only) {
+                        display(config::READ_ONLY);
+                        continue;
+                    }
 
                     match c {
-                        config::DOWN => {
-                            if on < ubt - 1 {
-                                on += 1;
-                                topics(&a2do, on)?;
-                            }
+                        config::DOWN if position(&tview, &on) + 1 < ubt => {
+                            on = tview[position(&tview, &on) + 1];
+                            topics(&a2do, on)?;
+                        }
+                        config::UP if position(&tview, &on) > 0 => {
+                            on = tview[position(&tview, &on) - 1];
+                            topics(&a2do, on)?;
+                        }
+                        config::SG if getch() == config::SG && ubt > 0 => {
+                            on = tview[0];
+                            topics(&a2do, on)?;
                         }
-                        config::UP => {
-                            if on > 0 {
-                                on -= 1;
-                                topics(&a2do, on)?;
+                        config::BG if ubt > 0 => {
+                            on = tview[ubt - 1];
+                            topics(&a2do, on)?;
+                        }
+                        config::SORT => {
+                            let sort = sort_topics(&mut a2do);
+                            tview = topic_rows(&a2do);
+
+                            clear();
+                            display(&format!("topics [{sort}]"));
+                            topics(&a2do, on)?;
+                        }
+                        config::ARCHIVE if ubt > 0 => {
+                            archive_topic(&mut a2do, on)?;
+
+                            tview = topic_rows(&a2do);
+                            ubt = tview.len();
+                            on = tview.first().copied().unwrap_or(0);
+                            clear();
+
+                            topics(&a2do, on)?;
+                        }
+                        config::ARCHIVES => {
+                            let mut on_archived = 0;
+                            let mut notice = None;
+
+                            clear();
+                            archive(&a2do, on_archived)?;
+
+                            loop {
+                                let (uba, days) = {
+                                    let ctx = a2do.lock().unwrap();
+                                    (ctx.archive.len(), ctx.auto_archive)
+                                };
+                                match (notice.take(), days) {
+                                    (Some(s), _) => display(s),
+                                    (_, Some(days)) => display(&format!(
+                                        "archive [auto-archive after {days} days]"
+                                    )),
+                                    (_, None) => display("archive [auto-archive off]"),
+                                }
+                                let c = getch();
+                                display_command(c, 1);
+                                if refused(c, read_only) {
+                                    notice = Some(config::READ_ONLY);
+                                    continue;
+                                }
+
+                                match c {
+                                    config::DOWN if on_archived + 1 < uba => {
+                                        on_archived += 1;
+                                        archive(&a2do, on_archived)?;
+                                    }
+                                    config::UP if on_archived > 0 => {
+                                        on_archived -= 1;
+                                        archive(&a2do, on_archived)?;
+                                    }
+                                    config::RESTORE if on_archived < uba => {
+                                        restore_topic(&mut a2do, on_archived)?;
+
+                                        on_archived = 0;
+                                        clear();
+
+                                        archive(&a2do, on_archived)?;
+                                    }
+                                    config::DELETE if on_archived < uba => {
+                                        delete_archived(&mut a2do, on_archived)?;
+
+                                        on_archived = 0;
+                                        clear();
+
+                                        archive(&a2do, on_archived)?;
+                                    }
+                                    config::MORE => {
+                                        set_auto_archive(
+                                            &mut a2do,
+                                            Some(days.map_or(1, |days| days + 1)),
+                                        );
+                                    }
+                                    config::LESS => {
+                                        set_auto_archive(
+                                            &mut a2do,
+                                            days.filter(|days| *days > 1).map(|days| days - 1),
+                                        );
+                                    }
+                                    config::ESC | config::EXIT => {
+                                        tview = topic_rows(&a2do);
+                                        ubt = tview.len();
+                                        on = tview.first().copied().unwrap_or(0);
+                                        clear();
+
+                                        display("topics");
+                                        topics(&a2do, on)?;
+
+                                        break;
+                                    }
+                                    _ => (),
+                                }
                             }
                         }
-                        config::SG => match getch() {
-                            config::SG => {
-                                on = 0;
-                                topics(&a2do, on)?;
+                        config::TAGS => {
+                            display_command(c, 1);
+
+                            let tag = input().trim().trim_start_matches('#').to_owned();
+                            let mut tagged = tagged_rows(&a2do, &tag);
+                            let mut on_tagged = 0;
+                            let mut notice = None;
+
+                            clear();
+                            tagged_items(&a2do, &tag, on_tagged)?;
+
+                            loop {
+                                match notice.take() {
+                                    Some(s) => display(s),
+                                    _ => display(&format!("#{tag}")),
+                                }
+                                let ubg = tagged.len();
+                                let c = getch();
+                                display_command(c, 1);
+                                if refused(c, read_only) {
+                                    notice = Some(config::READ_ONLY);
+                                    continue;
+                                }
+
+                                match c {
+                                    config::TAB if on_tagged < ubg => {
+                                        let (on_topic, at) = &tagged[on_tagged];
+                                        update_item(&mut a2do, *on_topic, at, |item| {
+                                            item.toggle(Utc::now())
+                                        })?;
+                                        clear();
+
+                                        tagged_items(&a2do, &tag, on_tagged)?;
+                                    }
+                                    config::EDIT if on_tagged < ubg => {
+                                        let at = tagged[on_tagged].clone();
+                                        let t = access_item(&mut a2do, at.0, &at.1)
+                                            .map(|item| item.text().to_owned())?;
+                                        let t = edit_line(t);
+
+                                        update_item(&mut a2do, at.0, &at.1, |item| {
+                                            item.edit(&t, Utc::now())
+                                        })?;
+
+                                        // the tag might have been edited away
+                                        tagged = tagged_rows(&a2do, &tag);
+                                        on_tagged = position(&tagged, &at);
+                                        clear();
+
+                                        tagged_items(&a2do, &tag, on_tagged)?;
+                                    }
+                                    config::DELETE if on_tagged < ubg => {
+                                        let (on_topic, at) = &tagged[on_tagged];
+                                        delete_item(&mut a2do, *on_topic, at)?;
+
+                                        tagged = tagged_rows(&a2do, &tag);
+                                        on_tagged = 0;
+                                        clear();
+
+                                        tagged_items(&a2do, &tag, on_tagged)?;
+                                    }
+                                    config::DOWN if on_tagged + 1 < ubg => {
+                                        on_tagged += 1;
+                                        tagged_items(&a2do, &tag, on_tagged)?;
+                                    }
+                                    config::UP if on_tagged > 0 => {
+                                        on_tagged -= 1;
+                                        tagged_items(&a2do, &tag, on_tagged)?;
+                                    }
+                                    config::SG if getch() == config::SG => {
+                                        on_tagged = 0;
+                                        tagged_items(&a2do, &tag, on_tagged)?;
+                                    }
+                                    config::BG if ubg > 0 => {
+                                        on_tagged = ubg - 1;
+                                        tagged_items(&a2do, &tag, on_tagged)?;
+                                    }
+                                    config::ESC | config::EXIT => {
+                                        clear();
+
+                                        display("topics");
+                                        topics(&a2do, on)?;
+
+                                        break;
+                                    }
+                                    _ => (),
+                                }
                             }
-                            _ => (),
-                        },
-                        config::BG => {
-                            on = ubt - 1;
+                        }
+                        config::IMPORT => {
+                            display_command(c, 1);
+                            display("import from");
+
+                            let path = input();
+                            let message = match exchange::import(path.trim(), Utc::now()) {
+                                Ok((imported, skipped)) => match skipped.len() {
+                                    0 => format!("imported {} todos", import(&mut a2do, imported)),
+                                    k => format!(
+                                        "imported {} todos, skipped {k} rows",
+                                        import(&mut a2do, imported)
+                                    ),
+                                },
+                                Err(_) => format!("could not import {path}"),
+                            };
+
+                            tview = topic_rows(&a2do);
+                            ubt = tview.len();
+                            clear();
+
+                            display(&message);
                             topics(&a2do, on)?;
                         }
-                        config::SELECT => {
-                            let ctx = a2do.lock().unwrap();
-                            let mut ub = unsafe {
-                                ctx.data
-                                    .get(ctx.idxs.get(on).unwrap())
-                                    .map(|v| v.len())
-                                    .unwrap_unchecked()
+                        config::EXPORT if ubt > 0 => {
+                            display_command(c, 1);
+                            let topic = export(&mut a2do, Some(on))?;
+                            let name = &topic[0].0;
+                            display(&format!("export {name} (* for all topics) to [{name}.md]"));
+
+                            let path = input();
+                            let (all, path) = match path.trim().strip_prefix('*') {
+                                Some(path) => (true, path.trim()),
+                                None => (false, path.trim()),
+                            };
+                            let (exported, path) = match (all, path) {
+                                (true, "") => (export(&mut a2do, None)?, "go22dos.md".to_owned()),
+                                (true, _) => (export(&mut a2do, None)?, path.to_owned()),
+                                (_, "") => (topic.clone(), format!("{name}.md")),
+                                _ => (topic.clone(), path.to_owned()),
+                            };
+                            let message = match exchange::export(&path, &exported) {
+                                Ok(()) => format!("exported to {path}"),
+                                Err(_) => format!("could not export to {path}"),
                             };
+                            clear();
+
+                            display(&message);
+                            topics(&a2do, on)?;
+                        }
+                        config::SELECT if ubt > 0 => {
+                            let ctx = a2do.lock().unwrap();
                             let topic = unsafe { ctx.idxs.get_unchecked(on) }.clone();
                             drop(ctx);
+                            let mut view = rows(&a2do, on);
+                            let mut ub = view.len();
                             let mut on_item = 0;
+                            let mut notice = None;
 
                             clear();
                             items(&a2do, on, on_item)?;
 
                             loop {
-                                display(&topic);
-                                let mut c = getch();
+                                let sort = a2do.lock().unwrap().view_of(on).sort;
+                                match (notice.take(), sort) {
+                                    (Some(s), _) => display(s),
+                                    (_, memory::Sort::Manual) => display(&topic),
+                                    (_, sort) => display(&format!("{topic} [{sort}]")),
+                                }
+                                if !read_only {
+                                    save_change(&a2do, &mut base, store.as_mut())?;
+                                }
+                                let Some(c) = wait(&mut watch) else {
+                                    let at = view
+                                        .get(on_item)
+                                        .and_then(|at| access_item(&mut a2do, on, at).ok());
+                                    if !reload(&mut a2do, &mut base, store.as_mut())? {
+                                        continue;
+                                    }
+                                    let Some(on_topic) = topic_on(&a2do, &topic) else {
+                                        // the topic is gone
+                                        on = topic_rows(&a2do).first().copied().unwrap_or(0);
+                                        clear();
+                                        topics(&a2do, on)?;
+
+                                        break;
+                                    };
+                                    on = on_topic;
+                                    view = rows(&a2do, on);
+                                    ub = view.len();
+                                    on_item = at
+                                        .and_then(|at| {
+                                            view.iter().position(|path| {
+                                                access_item(&mut a2do, on, path)
+                                                    .is_ok_and(|item| item.is(&at))
+                                            })
+                                        })
+                                        .unwrap_or(on_item.min(ub.saturating_sub(1)));
+                                    clear();
+
+                                    items(&a2do, on, on_item)?;
+                                    continue;
+                                };
                                 display_command(c, 1);
+                                if refused(c, read_only) {
+                                    notice = Some(config::READ_ONLY);
+                                    continue;
+                                }
 
                                 match c {
                                     config::APPEND => {
-                                        let (mut x, mut y) = (0, 0);
-                                        getmaxyx(stdscr(), &mut y, &mut x);
-                                        mvprintw(y / 2, (x / 2) as i32, "");
-
-                                        curs_set(CURSOR_VISIBILITY::CURSOR_VISIBLE);
-
-                                        let mut t = "".to_owned();
-                                        let mut n = 0;
-
-                                        loop {
-                                            c = getch();
-
-                                            match c {
-                                                config::ESC => break,
-                                                config::REMOVE => {
-                                                    t.pop();
-                                                    n -= 1;
-                                                    mvprintw(y / 2, (x / 2) + n as i32, " ");
-                                                    mvprintw(y / 2, (x / 2) as i32, &t);
-                                                }
-                                                _ => {
-                                                    t = format!("{}{}", t, c as u8 as char);
-                                                    n += 1;
-                                                    mvprintw(y / 2, (x / 2) as i32, &t);
-                                                }
-                                            }
-                                        }
+                                        let t = input();
+                                        add_item(&mut a2do, on, Item::new(t, Utc::now()))?;
 
-                                        attron(COLOR_PAIR(config::HIGHLIGHT_PAIR));
-                                        addstr(&t);
-                                        attroff(COLOR_PAIR(config::HIGHLIGHT_PAIR));
+                                        view = rows(&a2do, on);
+                                        ub = view.len();
 
-                                        add_item(&mut a2do, on, format!("0{}", t))?;
+                                        on_item = 0;
+                                        clear();
 
-                                        curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
+                                        items(&a2do, on, on_item)?;
+                                    }
+                                    config::TAB if on_item < ub => {
+                                        let at = view[on_item].clone();
+                                        update_item(&mut a2do, on, &at, |item| {
+                                            item.toggle(Utc::now())
+                                        })?;
 
-                                        let ctx = a2do.lock().unwrap();
-                                        ub = ctx
-                                            .data
-                                            .get(ctx.idxs.get(on).unwrap())
-                                            .map(|v| v.len())
-                                            .unwrap();
-                                        drop(ctx);
+                                        view = rows(&a2do, on);
+                                        ub = view.len();
+                                        on_item = on_item.min(ub.saturating_sub(1));
+                                        clear();
 
-                                        on_item = 0;
+                                        items(&a2do, on, on_item)?;
+                                    }
+                                    config::EDIT if on_item < ub => {
+                                        let at = view[on_item].clone();
+                                        let t = access_item(&mut a2do, on, &at)
+                                            .map(|item| item.text().to_owned())?;
+                                        let t = edit_line(t);
+
+                                        update_item(&mut a2do, on, &at, |item| {
+                                            item.edit(&t, Utc::now())
+                                        })?;
+
+                                        view = rows(&a2do, on);
+                                        ub = view.len();
+
+                                        on_item = position(&view, &at);
                                         clear();
 
                                         items(&a2do, on, on_item)?;
                                     }
-                                    config::TAB => {
-                                        access_item(&mut a2do, on, on_item).and_then(|item| {
-                                            delete_item(&mut a2do, on, on_item).unwrap();
-                                            add_item(&mut a2do, on, format!("1{}", &item[1..]))
+                                    config::NOTES if on_item < ub => {
+                                        let at = view[on_item].clone();
+                                        let notes = access_item(&mut a2do, on, &at)
+                                            .map(|item| item.notes)?;
+                                        let notes = edit_notes(&notes);
+
+                                        update_item(&mut a2do, on, &at, |item| {
+                                            item.annotate(notes, Utc::now())
                                         })?;
+
+                                        items(&a2do, on, on_item)?;
+                                    }
+                                    config::VISUAL if on_item < ub => {
+                                        let at = view[on_item].clone();
+                                        let document = access_item(&mut a2do, on, &at)
+                                            .map(|item| item.document())?;
+
+                                        match external_edit(&document, "txt") {
+                                            Ok(document) => {
+                                                update_item(&mut a2do, on, &at, |item| {
+                                                    item.revise(&document, Utc::now())
+                                                })?
+                                            }
+                                            Err(_) => notice = Some("could not run the editor"),
+                                        }
+
+                                        view = rows(&a2do, on);
+                                        ub = view.len();
+                                        on_item = position(&view, &at);
                                         clear();
 
                                         items(&a2do, on, on_item)?;
                                     }
-                                    config::EDIT => {
-                                        let mut t =
-                                            access_item(&mut a2do, on, on_item).map(|item| {
-                                                delete_item(&mut a2do, on, on_item).unwrap();
-                                                item[1..].to_owned()
-                                            })?;
-
-                                        let (mut x, mut y) = (0, 0);
-                                        getmaxyx(stdscr(), &mut y, &mut x);
-                                        let mut n = t.len() - 1;
-                                        let mut curat = n;
-                                        mvprintw(y / 2, (x / 2) + n as i32, &t);
-
-                                        curs_set(CURSOR_VISIBILITY::CURSOR_VISIBLE);
-
-                                        loop {
-                                            c = getch();
-
-                                            match c {
-                                                17 => match getch() {
-                                                    // ctrl
-                                                    103 => {
-                                                        // g
-                                                        curat -= 1;
-                                                        mvcur(
-                                                            y / 2,
-                                                            (x / 2) + (curat + 1) as i32,
-                                                            y / 2,
-                                                            (x / 2) + curat as i32,
-                                                        );
-                                                    }
-                                                    104 => {
-                                                        // h
-                                                        if curat < (t.len() - 1) {
-                                                            curat += 1;
-                                                            mvcur(
-                                                                y / 2,
-                                                                (x / 2) + (curat - 1) as i32,
-                                                                y / 2,
-                                                                (x / 2) + curat as i32,
-                                                            );
-                                                        }
-                                                    }
-                                                    _ => continue,
-                                                },
-                                                config::REMOVE => {
-                                                    //mvprintw(
-                                                    //    y / 2,
-                                                    //    (x / 2) + n as i32,
-                                                    //    &t.chars()
-                                                    //        .skip(curat)
-                                                    //        .next()
-                                                    //        .unwrap()
-                                                    //        .to_string(),
-                                                    //);
-                                                    //mvprintw(
-                                                    //    y / 2,
-                                                    //    (x / 2) + n as i32,
-                                                    //    &std::iter::repeat(" ")
-                                                    //        //.take(n + n - 1)
-                                                    //        .take(n+1)
-                                                    //        .collect::<String>(),
-                                                    //);
-                                                    //mvcur(
-                                                    //    y / 2,
-                                                    //    (x / 2) + (curat + 1) as i32,
-                                                    //    y / 2,
-                                                    //    (x / 2) + curat as i32,
-                                                    //);
-                                                    //mvprintw(y / 2, (x / 2) + n as i32, &t);
-                                                    for (i,c_) in t.chars().enumerate() {
-                                                        match i == curat {
-                                                            false => mvprintw(y / 2, (x / 2) + i as i32, c_.to_string().as_str()),
-                                                            true =>  mvprintw(y / 2, (x / 2) + i as i32, "")
-                                                        };
-                                                    }
-                                                    mvprintw(
-                                                        y / 2,
-                                                        (x / 2) + n as i32,
-                                                        &std::iter::repeat(" ")
-                                                            //.take(n + n - 1)
-                                                            .take(n+1)
-                                                            .collect::<String>(),
-                                                    );
-
-                                                    t.remove(curat);
-                                                    curat -= 1;
-                                                    n -= 1;
-                                                }
-                                                config::ESC => break,
-                                                _ => {
-                                                    t = format!("{}{}", t, c as u8 as char);
-                                                    n += 1;
-                                                    mvprintw(y / 2, (x / 2) as i32, &t);
-                                                }
+                                    config::BULK => {
+                                        let checklist =
+                                            markdown::checklist(&access_topic(&mut a2do, on)?);
+
+                                        match external_edit(&checklist, "md") {
+                                            Ok(checklist) => {
+                                                update_topic(&mut a2do, on, |items| {
+                                                    *items = markdown::merge(
+                                                        items,
+                                                        &checklist,
+                                                        Utc::now(),
+                                                    )
+                                                })?
                                             }
+                                            Err(_) => notice = Some("could not run the editor"),
                                         }
 
-                                        attron(COLOR_PAIR(config::HIGHLIGHT_PAIR));
-                                        addstr(&t);
-                                        attroff(COLOR_PAIR(config::HIGHLIGHT_PAIR));
+                                        view = rows(&a2do, on);
+                                        ub = view.len();
+                                        on_item = 0;
+                                        clear();
+
+                                        items(&a2do, on, on_item)?;
+                                    }
+                                    config::MORE | config::LESS if on_item < ub => {
+                                        let at = view[on_item].clone();
+                                        update_item(&mut a2do, on, &at, |item| match c {
+                                            config::MORE => item.raise(Utc::now()),
+                                            _ => item.lower(Utc::now()),
+                                        })?;
+
+                                        view = rows(&a2do, on);
+                                        on_item = position(&view, &at);
+                                        clear();
 
-                                        add_item(&mut a2do, on, format!("0{}", t))?;
+                                        items(&a2do, on, on_item)?;
+                                    }
+                                    config::FOLD if on_item < ub => {
+                                        let at = view[on_item].clone();
+                                        update_item(&mut a2do, on, &at, |item| {
+                                            item.collapsed =
+                                                !item.collapsed && !item.children.is_empty()
+                                        })?;
 
-                                        curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
+                                        view = rows(&a2do, on);
+                                        ub = view.len();
+                                        on_item = position(&view, &at);
+                                        clear();
 
-                                        let ctx = a2do.lock().unwrap();
-                                        ub = ctx
-                                            .data
-                                            .get(ctx.idxs.get(on).unwrap())
-                                            .map(|v| v.len())
-                                            .unwrap();
-                                        drop(ctx);
+                                        items(&a2do, on, on_item)?;
+                                    }
+                                    config::INDENT if sibling_above(&view, on_item).is_some() => {
+                                        let under = sibling_above(&view, on_item).unwrap();
+                                        let at = indent_item(&mut a2do, on, &view[on_item], under)?;
 
+                                        view = rows(&a2do, on);
+                                        ub = view.len();
+                                        on_item = position(&view, &at);
+                                        clear();
+
+                                        items(&a2do, on, on_item)?;
+                                    }
+                                    config::OUTDENT
+                                        if view.get(on_item).is_some_and(|at| at.len() > 1) =>
+                                    {
+                                        let at = outdent_item(&mut a2do, on, &view[on_item])?;
+
+                                        view = rows(&a2do, on);
+                                        ub = view.len();
+                                        on_item = position(&view, &at);
+                                        clear();
+
+                                        items(&a2do, on, on_item)?;
+                                    }
+                                    config::DELETE if on_item < ub => {
+                                        delete_item(&mut a2do, on, &view[on_item])?;
+
+                                        view = rows(&a2do, on);
+                                        ub = view.len();
                                         on_item = 0;
                                         clear();
 
                                         items(&a2do, on, on_item)?;
                                     }
-                                    config::DELETE => {
-                                        delete_item(&mut a2do, on, on_item)?;
+                                    config::HIDE => {
+                                        hide_done(&mut a2do, on)?;
 
-                                        let ctx = a2do.lock().unwrap();
-                                        ub = ctx
-                                            .data
-                                            .get(ctx.idxs.get(on).unwrap())
-                                            .map(|v| v.len())
-                                            .unwrap();
-                                        drop(ctx);
+                                        view = rows(&a2do, on);
+                                        ub = view.len();
                                         on_item = 0;
                                         clear();
 
                                         items(&a2do, on, on_item)?;
                                     }
-                                    config::DOWN => {
-                                        if on_item < ub - 1 {
-                                            on_item += 1;
-                                            items(&a2do, on, on_item)?;
-                                        }
+                                    config::SORT => {
+                                        sort_items(&mut a2do, on)?;
+
+                                        view = rows(&a2do, on);
+                                        on_item = 0;
+                                        clear();
+
+                                        items(&a2do, on, on_item)?;
                                     }
-                                    config::UP => {
-                                        if on_item > 0 {
-                                            on_item -= 1;
-                                            items(&a2do, on, on_item)?;
-                                        }
+                                    config::REMEMBER => {
+                                        notice = Some(match remember_views(&mut a2do) {
+                                            true => "views are remembered",
+                                            _ => "views are forgotten",
+                                        });
                                     }
-                                    config::SG => match getch() {
-                                        config::SG => {
-                                            on_item = 0;
-                                            items(&a2do, on, on_item)?;
-                                        }
-                                        _ => (),
-                                    },
-                                    config::BG => {
+                                    config::DOWN if on_item + 1 < ub => {
+                                        on_item += 1;
+                                        items(&a2do, on, on_item)?;
+                                    }
+                                    config::UP if on_item > 0 => {
+                                        on_item -= 1;
+                                        items(&a2do, on, on_item)?;
+                                    }
+                                    config::SG if getch() == config::SG => {
+                                        on_item = 0;
+                                        items(&a2do, on, on_item)?;
+                                    }
+                                    config::BG if ub > 0 => {
                                         on_item = ub - 1;
                                         items(&a2do, on, on_item)?;
                                     }
@@ -462,64 +1391,32 @@ fn main() -> Result<()> {
                                     _ => (),
                                 }
                             }
+                            // topics might have changed on disk meanwhile
+                            tview = topic_rows(&a2do);
+                            ubt = tview.len();
                         }
                         config::APPEND => {
                             display_command(c, 1);
 
-                            let (mut x, mut y) = (0, 0);
-                            getmaxyx(stdscr(), &mut y, &mut x);
-                            mvprintw(y / 2, (x / 2) as i32, "");
-
-                            curs_set(CURSOR_VISIBILITY::CURSOR_VISIBLE);
-
-                            let mut t = "".to_owned();
-                            let mut n = 0;
-
-                            loop {
-                                c = getch();
-
-                                match c {
-                                    config::ESC => break,
-                                    config::REMOVE => {
-                                        t.pop();
-                                        n -= 1;
-                                        mvprintw(y / 2, (x / 2) + n as i32, " ");
-                                        mvprintw(y / 2, (x / 2) as i32, &t);
-                                    }
-                                    _ => {
-                                        t = format!("{}{}", t, c as u8 as char);
-                                        n += 1;
-                                        mvprintw(y / 2, (x / 2) as i32, &t);
-                                    }
-                                }
-                            }
-
-                            attron(COLOR_PAIR(config::HIGHLIGHT_PAIR));
-                            addstr(&t);
-                            attroff(COLOR_PAIR(config::HIGHLIGHT_PAIR));
-
+                            let t = input();
                             add_topic(&mut a2do, t.clone())?;
 
-                            curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
+                            tview = topic_rows(&a2do);
+                            ubt = tview.len();
 
-                            let ctx = a2do.lock().unwrap();
-                            ubt = ctx.idxs.len();
-                            drop(ctx);
-
-                            on = 0;
+                            on = tview.first().copied().unwrap_or(0);
 
                             clear();
 
                             topics(&a2do, on)?;
                         }
-                        config::DELETE => {
+                        config::DELETE if ubt > 0 => {
                             delete_topic(&mut a2do, on)?;
 
-                            let ctx = a2do.lock().unwrap();
-                            ubt = ctx.idxs.len();
-                            drop(ctx);
+                            tview = topic_rows(&a2do);
+                            ubt = tview.len();
 
-                            on = 0;
+                            on = tview.first().copied().unwrap_or(0);
                             clear();
 
                             topics(&a2do, on)?;
@@ -533,7 +1430,10 @@ fn main() -> Result<()> {
                 }
             }
             config::QUIT | config::ESC => {
-                save(&a2do, &file)?;
+                if !read_only {
+                    auto_archive(&mut a2do);
+                    save(&a2do, store.as_mut())?;
+                }
                 flash();
                 endwin();
                 return Ok(());
@@ -0,0 +1,271 @@
+//! Items as GitHub-style Markdown checklists, subtasks indented by two spaces,
+//! and topics as headings:
+//!
+//! ```text
+//! # topic
+//!
+//! - [ ] open item due:2026-11-01
+//!   - [x] done subtask
+//! ```
+use chrono::{DateTime, Utc};
+
+use crate::item::{self, Item};
+
+pub fn checklist(items: &[Item]) -> String {
+    let mut s = String::new();
+    write_checklist(items, 0, &mut s);
+    s
+}
+
+fn write_checklist(items: &[Item], depth: usize, s: &mut String) {
+    items.iter().for_each(|item| {
+        let status = match item.is_done() {
+            true => 'x',
+            _ => ' ',
+        };
+        s.push_str(&format!(
+            "{}- [{status}] {}\n",
+            "  ".repeat(depth),
+            item.line()
+        ));
+        write_checklist(&item.children, depth + 1, s);
+    });
+}
+
+/// A list item of a checklist: its depth, whether it is ticked off and its
+/// line. List items without a checkbox are none.
+pub fn entry(line: &str) -> Option<(usize, bool, &str)> {
+    let text = line.trim_start();
+    let indent = line[..line.len() - text.len()]
+        .chars()
+        .map(|c| match c {
+            '\t' => 4,
+            _ => 1,
+        })
+        .sum::<usize>();
+    let text = text
+        .strip_prefix("- ")
+        .or_else(|| text.strip_prefix("* "))
+        .or_else(|| text.strip_prefix("+ "))?;
+    let (done, text) = match text.get(..3) {
+        Some("[ ]") => (false, &text[3..]),
+        Some("[x]" | "[X]") => (true, &text[3..]),
+        _ => return None,
+    };
+    let text = text.trim();
+    match text.is_empty() {
+        true => None,
+        _ => Some((indent / 2, done, text)),
+    }
+}
+
+/// Builds the tree of subtasks from items and their depths.
+pub fn nest(entries: impl IntoIterator<Item = (usize, Item)>) -> Vec<Item> {
+    fn close(stack: &mut Vec<(usize, Item)>, roots: &mut Vec<Item>) {
+        if let Some((_, item)) = stack.pop() {
+            match stack.last_mut() {
+                Some((_, parent)) => parent.children.push(item),
+                None => roots.push(item),
+            }
+        }
+    }
+
+    let mut roots = vec![];
+    let mut stack = vec![];
+    for (depth, item) in entries {
+        while stack.last().is_some_and(|(d, _)| *d >= depth) {
+            close(&mut stack, &mut roots);
+        }
+        stack.push((depth, item));
+    }
+    while !stack.is_empty() {
+        close(&mut stack, &mut roots);
+    }
+    roots
+}
+
+/// Topics as headings, each followed by the checklist of its items.
+pub fn export(topics: &[(String, Vec<Item>)]) -> String {
+    topics
+        .iter()
+        .map(|(topic, items)| format!("# {topic}\n\n{}", checklist(items)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn heading(line: &str) -> Option<&str> {
+    let text = line.trim_start_matches('#');
+    match text.len() < line.len() && text.starts_with(' ') {
+        true => Some(text.trim()).filter(|text| !text.is_empty()),
+        _ => None,
+    }
+}
+
+/// Headings with checklist items below them become topics, the items theirs.
+/// Items before the first heading go to `topic`, anything else, code blocks
+/// included, is skipped.
+pub fn import(s: &str, topic: &str, at: DateTime<Utc>) -> Vec<(String, Vec<Item>)> {
+    let mut topics: Vec<(String, Vec<(usize, Item)>)> = vec![];
+    let mut fenced = false;
+    s.lines().for_each(|line| {
+        let fence = line.trim_start();
+        if fence.starts_with("```") || fence.starts_with("~~~") {
+            fenced = !fenced;
+            return;
+        }
+        if fenced {
+            return;
+        }
+        if let Some(heading) = heading(line) {
+            topics.push((heading.to_owned(), vec![]));
+        } else if let Some((depth, done, text)) = entry(line) {
+            if topics.is_empty() {
+                topics.push((topic.to_owned(), vec![]));
+            }
+            let mut item = Item::new(text, at);
+            if done {
+                item.toggle(at);
+            }
+            if let Some((_, entries)) = topics.last_mut() {
+                entries.push((depth, item));
+            }
+        }
+    });
+    topics
+        .into_iter()
+        .filter(|(_, entries)| !entries.is_empty())
+        .map(|(topic, entries)| (topic, nest(entries)))
+        .collect()
+}
+
+/// How much of two texts an edit in between left untouched.
+fn kept(a: &str, b: &str) -> usize {
+    let prefix = a.chars().zip(b.chars()).take_while(|(x, y)| x == y).count();
+    let suffix = a
+        .chars()
+        .rev()
+        .zip(b.chars().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    (prefix + suffix).min(a.chars().count().min(b.chars().count()))
+}
+
+/// Takes an edited checklist of `items` back. Lines are matched to the items
+/// they came from by their text, or else to an item most of whose text they
+/// kept, so that items keep what the checklist does not show. Unmatched lines
+/// become new items, unmatched items are dropped.
+pub fn merge(items: &[Item], checklist: &str, at: DateTime<Utc>) -> Vec<Item> {
+    let old = item::walk(items);
+    let entries = checklist.lines().filter_map(entry).collect::<Vec<_>>();
+    let texts = entries
+        .iter()
+        .map(|(_, _, line)| Item::new(line, at).text().to_owned())
+        .collect::<Vec<_>>();
+
+    let mut used = vec![false; old.len()];
+    let mut matched = vec![None; entries.len()];
+    texts.iter().enumerate().for_each(|(i, text)| {
+        matched[i] = (0..old.len()).find(|j| !used[*j] && old[*j].text() == text);
+        matched[i].iter().for_each(|j| used[*j] = true);
+    });
+    texts.iter().enumerate().for_each(|(i, text)| {
+        if matched[i].is_some() {
+            return;
+        }
+        matched[i] = (0..old.len())
+            .filter(|j| !used[*j])
+            .map(|j| (kept(old[j].text(), text), j))
+            .filter(|(k, j)| *k > 0 && 2 * k >= old[*j].text().chars().count())
+            .max_by_key(|(k, _)| *k)
+            .map(|(_, j)| j);
+        matched[i].iter().for_each(|j| used[*j] = true);
+    });
+
+    let merged = entries
+        .iter()
+        .zip(matched)
+        .map(|((depth, done, line), matched)| {
+            let mut item = match matched {
+                Some(j) => {
+                    let mut item = old[j].clone();
+                    item.children = vec![];
+                    if item.line() != *line {
+                        item.rewrite(line, at);
+                    }
+                    item
+                }
+                None => Item::new(line, at),
+            };
+            if item.is_done() != *done {
+                item.toggle(at);
+            }
+            (*depth, item)
+        });
+
+    nest(merged)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn checklists() {
+        let at = Utc::now();
+        let mut a = Item::new("a due:2026-11-01", at);
+        a.children = vec![Item::from("1b")];
+        let items = vec![a, Item::from("0c")];
+        let s = checklist(&items);
+        assert_eq!(s, "- [ ] a due:2026-11-01\n  - [x] b\n- [ ] c\n");
+        assert_eq!(merge(&items, &s, at), items);
+
+        assert_eq!(entry("\t* [X] done"), Some((2, true, "done")));
+        assert_eq!(entry("- plain"), None);
+        assert_eq!(entry("- [ ] "), None);
+        assert_eq!(entry("# heading"), None);
+    }
+
+    #[test]
+    fn files() {
+        let at = Utc::now();
+        let s = "- [ ] loose\n- plain\n\n## Work\n\nsome text\n- [x] report\n  - [ ] figures\n\
+                 # Usage\n\n```sh\n# not a heading\n- [ ] not an item\n```\n# #home\n* [ ] #tidy up\n";
+        let topics = import(s, "readme", at);
+        let names = topics
+            .iter()
+            .map(|(topic, _)| topic.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["readme", "Work", "#home"]);
+        assert_eq!(topics[0].1.len(), 1);
+        assert!(topics[1].1[0].is_done());
+        assert_eq!(topics[1].1[0].children[0].text(), "figures");
+        assert_eq!(topics[2].1[0].tags(), vec!["tidy"]);
+
+        let s = export(&topics);
+        assert!(s.starts_with("# readme\n\n- [ ] loose\n\n# Work\n"));
+        assert_eq!(import(&s, "other", at), topics);
+    }
+
+    #[test]
+    fn edits_are_merged() {
+        let then = Utc::now();
+        let mut a = Item::new("a", then);
+        a.notes = "keep me".to_owned();
+        let items = vec![a, Item::new("b", then), Item::new("c", then)];
+
+        let now = then + chrono::Duration::days(1);
+        // c moves up and is ticked off, a is renamed, b is deleted, d is new
+        let merged = merge(&items, "- [x] c\n- [ ] a2\n  - [ ] d\n", now);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].text(), "c");
+        assert!(merged[0].is_done());
+        assert_eq!(merged[0].created, Some(then));
+        assert_eq!(merged[1].text(), "a2");
+        assert_eq!(merged[1].notes, "keep me");
+        assert_eq!(merged[1].children[0].text(), "d");
+        assert_eq!(merged[1].children[0].created, Some(now));
+
+        let merged = merge(&merged, "- [x] c\n- [ ] a2 and more\n", now);
+        assert_eq!(merged[1].notes, "keep me");
+    }
+}
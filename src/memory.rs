@@ -1,14 +1,18 @@
-use serde::{de::DeserializeOwned, Serialize};
-use std::fs::File;
-use std::io::prelude::*;
+use chrono::{DateTime, Duration, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
 use thiserror::Error;
 
 use std::sync::{Arc, Mutex, MutexGuard};
 
+use crate::backend::Backend;
+use crate::item::{self, Item};
+
 #[derive(Debug, Error)]
 pub enum MemoryError {
     JsonError(#[from] serde_json::Error),
     IoError(#[from] std::io::Error),
+    SqliteError(#[from] rusqlite::Error),
     Unknown,
 }
 impl std::fmt::Display for MemoryError {
@@ -18,7 +22,7 @@ impl std::fmt::Display for MemoryError {
 }
 
 type Result<T> = std::result::Result<T, MemoryError>;
-type Data<T, I> = std::collections::HashMap<T, Vec<I>>;
+type Data<T, I> = HashMap<T, Vec<I>>;
 
 pub trait Serializable:
     Eq
@@ -44,6 +48,107 @@ impl<T> Serializable for T where
 {
 }
 
+/// Order in which the items of a topic are displayed. Sorting only affects
+/// the view, the stored (manual) order is left untouched.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sort {
+    #[default]
+    Manual,
+    /// open items first
+    Status,
+    Alphabetical,
+    /// newest items first
+    Created,
+    /// soonest due items first
+    Due,
+    /// highest priority first
+    Priority,
+}
+
+impl Sort {
+    pub fn next(self) -> Self {
+        match self {
+            Sort::Manual => Sort::Status,
+            Sort::Status => Sort::Alphabetical,
+            Sort::Alphabetical => Sort::Created,
+            Sort::Created => Sort::Due,
+            Sort::Due => Sort::Priority,
+            Sort::Priority => Sort::Manual,
+        }
+    }
+}
+
+impl std::fmt::Display for Sort {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = match self {
+            Sort::Manual => "manual",
+            Sort::Status => "status",
+            Sort::Alphabetical => "alphabetical",
+            Sort::Created => "created",
+            Sort::Due => "due",
+            Sort::Priority => "priority",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Order in which topics are displayed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TopicSort {
+    #[default]
+    Manual,
+    Alphabetical,
+    /// least completed topics first
+    Ratio,
+    /// topics with the most open items first
+    Open,
+}
+
+impl TopicSort {
+    pub fn next(self) -> Self {
+        match self {
+            TopicSort::Manual => TopicSort::Alphabetical,
+            TopicSort::Alphabetical => TopicSort::Ratio,
+            TopicSort::Ratio => TopicSort::Open,
+            TopicSort::Open => TopicSort::Manual,
+        }
+    }
+}
+
+impl std::fmt::Display for TopicSort {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = match self {
+            TopicSort::Manual => "manual",
+            TopicSort::Alphabetical => "alphabetical",
+            TopicSort::Ratio => "ratio",
+            TopicSort::Open => "open",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Per topic view settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct View {
+    #[serde(default)]
+    pub hide_done: bool,
+    #[serde(default)]
+    pub sort: Sort,
+}
+
+/// A topic moved out of `idxs`, together with its items.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "K: Serializable, V: Serializable")]
+pub struct Archived<K, V> {
+    pub topic: K,
+    pub items: Vec<V>,
+    #[serde(default)]
+    pub created: Option<DateTime<Utc>>,
+    pub archived: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Memory<K, V>
 where
@@ -52,19 +157,318 @@ where
 {
     pub data: Data<K, V>,
     pub idxs: Vec<K>,
+    /// When topics were created.
+    pub created: HashMap<K, DateTime<Utc>>,
+    pub views: HashMap<K, View>,
+    pub topic_sort: TopicSort,
+    pub remember_views: bool,
+    pub archive: Vec<Archived<K, V>>,
+    /// Since when a topic has been fully done, as far as we have seen, for
+    /// topics with items ticked off before completion times were kept.
+    pub done_since: HashMap<K, DateTime<Utc>>,
+    /// Archive topics which have been fully done for this many days.
+    pub auto_archive: Option<u32>,
+}
+
+/// On disk layout of a store.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "K: Serializable, V: Serializable")]
+struct Store<K, V> {
+    data: Data<K, V>,
+    #[serde(default)]
+    idxs: Vec<K>,
+    #[serde(default)]
+    created: HashMap<K, DateTime<Utc>>,
+    #[serde(default)]
+    views: HashMap<K, View>,
+    #[serde(default)]
+    topic_sort: TopicSort,
+    #[serde(default)]
+    remember_views: bool,
+    #[serde(default)]
+    archive: Vec<Archived<K, V>>,
+    #[serde(default)]
+    done_since: HashMap<K, DateTime<Utc>>,
+    #[serde(default)]
+    auto_archive: Option<u32>,
+}
+
+impl<K: Serializable, V: Serializable> From<Store<K, V>> for Memory<K, V> {
+    fn from(store: Store<K, V>) -> Self {
+        Self {
+            data: store.data,
+            idxs: store.idxs,
+            created: store.created,
+            views: store.views,
+            topic_sort: store.topic_sort,
+            remember_views: store.remember_views,
+            archive: store.archive,
+            done_since: store.done_since,
+            auto_archive: store.auto_archive,
+        }
+    }
+}
+
+/// Stores written before topic order and views were persisted are a plain
+/// map from topics to items.
+#[derive(Deserialize)]
+#[serde(untagged, bound = "K: Serializable, V: Serializable")]
+enum Stored<K, V> {
+    Store(Box<Store<K, V>>),
+    Legacy(Data<K, V>),
+}
+
+/// When the last of the done `items` was ticked off, none if any of them was
+/// ticked off before completion times were kept.
+fn finished(items: &[Item]) -> Option<DateTime<Utc>> {
+    item::walk(items)
+        .into_iter()
+        .filter(|item| item.is_done())
+        .map(|item| item.completed)
+        .collect::<Option<Vec<_>>>()?
+        .into_iter()
+        .max()
+}
+
+/// Share of done items, subtasks rolled up into their parents, `NaN` for
+/// topics without items.
+pub fn ratio(items: &[Item]) -> f32 {
+    let n = items.len() as f32;
+    let m = items.iter().map(Item::progress).sum::<f32>();
+    m / n
+}
+
+/// Appends the paths of `items` below `path` in the order they are displayed
+/// and returns how many done items were folded away.
+fn layout(items: &[Item], view: &View, path: &mut Vec<usize>, rows: &mut Vec<Vec<usize>>) -> usize {
+    let mut order = (0..items.len()).collect::<Vec<_>>();
+    match view.sort {
+        Sort::Manual => (),
+        Sort::Status => order.sort_by_key(|i| items[*i].is_done()),
+        Sort::Alphabetical => order.sort_by_key(|i| items[*i].text().to_lowercase()),
+        Sort::Created => order.sort_by_key(|i| std::cmp::Reverse(items[*i].created)),
+        Sort::Due => order.sort_by_key(|i| (items[*i].due.is_none(), items[*i].due)),
+        Sort::Priority => order.sort_by_key(|i| (items[*i].priority.is_none(), items[*i].priority)),
+    }
+    let mut folded = 0;
+    for i in order {
+        let item = &items[i];
+        if view.hide_done && item.is_done() {
+            folded += 1;
+            continue;
+        }
+        path.push(i);
+        rows.push(path.clone());
+        if !item.collapsed {
+            folded += layout(&item.children, view, path, rows);
+        }
+        path.pop();
+    }
+    folded
 }
 
 impl<K: Serializable, V: Serializable> Memory<K, V> {
-    #[allow(dead_code)]
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self {
-            data: std::collections::HashMap::new(),
+            data: HashMap::new(),
             idxs: Vec::new(),
+            created: HashMap::new(),
+            views: HashMap::new(),
+            topic_sort: TopicSort::default(),
+            remember_views: false,
+            archive: Vec::new(),
+            done_since: HashMap::new(),
+            auto_archive: None,
         }
     }
 
-    fn serialize(&self) -> Option<String> {
-        serde_json::to_string(&self.data).ok()
+    fn store(&self, data: Data<K, V>) -> Store<K, V> {
+        Store {
+            data,
+            idxs: self.idxs.clone(),
+            created: self.created.clone(),
+            views: match self.remember_views {
+                true => self.views.clone(),
+                _ => HashMap::new(),
+            },
+            topic_sort: match self.remember_views {
+                true => self.topic_sort,
+                _ => TopicSort::default(),
+            },
+            remember_views: self.remember_views,
+            archive: self.archive.clone(),
+            done_since: self.done_since.clone(),
+            auto_archive: self.auto_archive,
+        }
+    }
+
+    pub fn serialize(&self) -> Option<String> {
+        serde_json::to_string(&self.store(self.data.clone())).ok()
+    }
+
+    /// Brings `idxs` in line with the topics: the order is kept, topics
+    /// unknown to it go last.
+    pub fn reindex(&mut self) {
+        let data = &self.data;
+        self.idxs.retain(|topic| data.contains_key(topic));
+        let mut missing = data
+            .keys()
+            .filter(|topic| !self.idxs.contains(*topic))
+            .cloned()
+            .collect::<Vec<_>>();
+        self.idxs.append(&mut missing);
+    }
+
+    /// Everything but the items, as it is stored.
+    pub fn meta(&self) -> Option<serde_json::Value> {
+        let mut meta = serde_json::to_value(self.store(HashMap::new())).ok()?;
+        meta.as_object_mut()?.remove("data");
+        Some(meta)
+    }
+
+    /// Replaces everything but the items with `meta`.
+    pub fn set_meta(&mut self, mut meta: serde_json::Value) -> Result<()> {
+        meta.as_object_mut()
+            .ok_or(MemoryError::Unknown)?
+            .insert("data".to_owned(), serde_json::json!({}));
+        let store = serde_json::from_value::<Store<K, V>>(meta)?;
+        let data = std::mem::take(&mut self.data);
+        *self = Memory {
+            data,
+            ..Memory::from(store)
+        };
+        Ok(())
+    }
+
+    fn archive_topic(&mut self, on: usize, at: DateTime<Utc>) -> Option<()> {
+        let topic = self.idxs.get(on)?.clone();
+        let items = self.data.remove(&topic)?;
+        self.idxs.remove(on);
+        self.views.remove(&topic);
+        self.done_since.remove(&topic);
+        let created = self.created.remove(&topic);
+        self.archive.push(Archived {
+            topic,
+            items,
+            created,
+            archived: at,
+        });
+        Some(())
+    }
+
+    pub fn view_of(&self, on_topic: usize) -> View {
+        self.idxs
+            .get(on_topic)
+            .and_then(|topic| self.views.get(topic))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+impl<K: Serializable> Memory<K, Item> {
+    /// Archives the topics which have been fully done for longer than
+    /// `auto_archive` days, counted from the last item ticked off.
+    fn settle(&mut self, now: DateTime<Utc>) -> usize {
+        let done = self
+            .idxs
+            .iter()
+            .filter_map(|topic| {
+                let items = self.data.get(topic)?;
+                (ratio(items) == 1.0).then(|| (topic.clone(), finished(items)))
+            })
+            .collect::<Vec<_>>();
+        self.done_since.retain(|topic, _| {
+            done.iter()
+                .any(|(t, finished)| t == topic && finished.is_none())
+        });
+        done.iter()
+            .filter(|(_, finished)| finished.is_none())
+            .for_each(|(topic, _)| {
+                self.done_since.entry(topic.clone()).or_insert(now);
+            });
+
+        let Some(days) = self.auto_archive else {
+            return 0;
+        };
+        let expired = done
+            .into_iter()
+            .filter_map(|(topic, finished)| {
+                let since = finished.or(self.done_since.get(&topic).copied())?;
+                (now - since >= Duration::days(days.into())).then_some(topic)
+            })
+            .collect::<Vec<_>>();
+        expired
+            .into_iter()
+            .filter_map(|topic| {
+                let on = self.idxs.iter().position(|t| *t == topic)?;
+                self.archive_topic(on, now)
+            })
+            .count()
+    }
+
+    /// Indices into `idxs` in the order topics are displayed.
+    pub fn topic_view(&self) -> Vec<usize> {
+        let items = |i: &usize| {
+            self.data
+                .get(&self.idxs[*i])
+                .map(|items| items.as_slice())
+                .unwrap_or_default()
+        };
+        let mut rows = (0..self.idxs.len()).collect::<Vec<_>>();
+        match self.topic_sort {
+            TopicSort::Manual => (),
+            TopicSort::Alphabetical => rows.sort_by_key(|i| self.idxs[*i].as_ref().to_lowercase()),
+            TopicSort::Ratio => rows.sort_by(|i, j| ratio(items(i)).total_cmp(&ratio(items(j)))),
+            TopicSort::Open => rows.sort_by_key(|i| {
+                std::cmp::Reverse(
+                    item::walk(items(i))
+                        .iter()
+                        .filter(|item| !item.is_done())
+                        .count(),
+                )
+            }),
+        }
+        rows
+    }
+
+    /// Number of done items folded away in a topic.
+    pub fn folded(&self, on_topic: usize) -> usize {
+        let view = self.view_of(on_topic);
+        self.idxs
+            .get(on_topic)
+            .and_then(|topic| self.data.get(topic))
+            .map(|items| layout(items, &view, &mut vec![], &mut vec![]))
+            .unwrap_or(0)
+    }
+
+    /// Items of all topics carrying a tag, as topic and path, in the order
+    /// topics are displayed.
+    pub fn tagged(&self, tag: &str) -> Vec<(usize, Vec<usize>)> {
+        self.topic_view()
+            .into_iter()
+            .flat_map(|on| {
+                let items = &self.data[&self.idxs[on]];
+                item::paths(items)
+                    .into_iter()
+                    .filter(|path| item::get(items, path).is_some_and(|item| item.has_tag(tag)))
+                    .map(|path| (on, path))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Paths of the items of a topic in the order they are displayed.
+    pub fn view(&self, on_topic: usize) -> Vec<Vec<usize>> {
+        let view = self.view_of(on_topic);
+        let mut rows = vec![];
+        if let Some(items) = self
+            .idxs
+            .get(on_topic)
+            .and_then(|topic| self.data.get(topic))
+        {
+            layout(items, &view, &mut vec![], &mut rows);
+        }
+        rows
     }
 }
 
@@ -73,11 +477,25 @@ where
     K: Serializable,
     V: Serializable,
 {
-    let contents = std::fs::read_to_string(path).ok()?.clone();
-    serde_json::from_str::<Data<K, V>>(&contents)
-        .map(|data| {
-            let idxs = data.keys().cloned().collect::<Vec<_>>();
-            Memory { data, idxs }
+    parse(&std::fs::read_to_string(path).ok()?)
+}
+
+pub fn parse<K, V>(contents: &str) -> Option<Memory<K, V>>
+where
+    K: Serializable,
+    V: Serializable,
+{
+    serde_json::from_str::<Stored<K, V>>(contents)
+        .map(|stored| {
+            let mut mem = match stored {
+                Stored::Store(store) => Memory::from(*store),
+                Stored::Legacy(data) => Memory {
+                    data,
+                    ..Memory::new()
+                },
+            };
+            mem.reindex();
+            mem
         })
         .ok()
 }
@@ -88,30 +506,52 @@ where
     V: Serializable,
 {
     fn lock_unwrap(&self) -> MutexGuard<'_, Memory<K, V>>;
-    fn save(&self, to: String) -> Result<()>;
+    fn save(&self, to: &mut dyn Backend<K, V>) -> Result<()>;
     fn add_item(&mut self, on: usize, item: V) -> Result<()>;
-    fn delete_item(&mut self, on_topic: usize, on_item: usize) -> Result<V>;
-    fn access_item(&mut self, on_topic: usize, on_item: usize) -> Result<V>;
+    fn delete_item(&mut self, on_topic: usize, on_item: &[usize]) -> Result<V>;
+    fn access_item(&mut self, on_topic: usize, on_item: &[usize]) -> Result<V>;
+    fn update_item(
+        &mut self,
+        on_topic: usize,
+        on_item: &[usize],
+        update: impl FnOnce(&mut V),
+    ) -> Result<()>;
+    fn access_topic(&mut self, on_topic: usize) -> Result<Vec<V>>;
+    fn update_topic(&mut self, on_topic: usize, update: impl FnOnce(&mut Vec<V>)) -> Result<()>;
+    fn indent_item(
+        &mut self,
+        on_topic: usize,
+        on_item: &[usize],
+        under: usize,
+    ) -> Result<Vec<usize>>;
+    fn outdent_item(&mut self, on_topic: usize, on_item: &[usize]) -> Result<Vec<usize>>;
     fn add_topic(&mut self, topic_id: impl Into<K>) -> Result<()>;
     fn delete_topic(&mut self, on: usize) -> Result<()>;
+    fn hide_done(&mut self, on_topic: usize) -> Result<bool>;
+    fn sort_items(&mut self, on_topic: usize) -> Result<Sort>;
+    fn sort_topics(&mut self) -> TopicSort;
+    fn remember_views(&mut self) -> bool;
+    fn archive_topic(&mut self, on: usize) -> Result<()>;
+    fn restore_topic(&mut self, on_archived: usize) -> Result<()>;
+    fn delete_archived(&mut self, on_archived: usize) -> Result<()>;
+    fn set_auto_archive(&mut self, days: Option<u32>);
+    fn auto_archive(&mut self) -> usize;
+    fn import(&mut self, topics: Vec<(K, Vec<V>)>) -> usize;
+    fn export(&mut self, on: Option<usize>) -> Result<Vec<(K, Vec<V>)>>;
 }
-impl<K, V> MemoryManagement<K, V> for Arc<Mutex<Memory<K, V>>>
+impl<K> MemoryManagement<K, Item> for Arc<Mutex<Memory<K, Item>>>
 where
     K: Serializable + std::fmt::Debug,
-    V: Serializable + std::fmt::Debug,
 {
-    fn lock_unwrap(&self) -> MutexGuard<'_, Memory<K, V>> {
+    fn lock_unwrap(&self) -> MutexGuard<'_, Memory<K, Item>> {
         self.lock().expect("mutex lock is poisoned")
     }
 
-    fn save(&self, to: String) -> Result<()> {
-        let ctx = self.lock_unwrap();
-        let mut f = File::create(to).map_err(MemoryError::IoError)?;
-        let data = ctx.serialize().ok_or(MemoryError::Unknown)?;
-        f.write_all(data.as_bytes()).map_err(MemoryError::IoError)
+    fn save(&self, to: &mut dyn Backend<K, Item>) -> Result<()> {
+        to.save(&self.lock_unwrap())
     }
 
-    fn add_item(&mut self, on_topic: usize, item: V) -> Result<()> {
+    fn add_item(&mut self, on_topic: usize, item: Item) -> Result<()> {
         let mut ctx = self.lock_unwrap();
         let idxs = ctx.idxs.clone();
         idxs.get(on_topic)
@@ -120,23 +560,78 @@ where
             .ok_or(MemoryError::Unknown)
     }
 
-    fn access_item(&mut self, on_topic: usize, on_item: usize) -> Result<V> {
+    fn access_item(&mut self, on_topic: usize, on_item: &[usize]) -> Result<Item> {
+        let mut ctx = self.lock_unwrap();
+        let idxs = ctx.idxs.clone();
+        idxs.get(on_topic)
+            .and_then(|topic| ctx.data.get_mut(topic))
+            .and_then(|items| item::get(items, on_item))
+            .ok_or(MemoryError::Unknown)
+            .cloned()
+    }
+
+    fn update_item(
+        &mut self,
+        on_topic: usize,
+        on_item: &[usize],
+        update: impl FnOnce(&mut Item),
+    ) -> Result<()> {
+        let mut ctx = self.lock_unwrap();
+        let idxs = ctx.idxs.clone();
+        idxs.get(on_topic)
+            .and_then(|topic| ctx.data.get_mut(topic))
+            .and_then(|items| item::get_mut(items, on_item))
+            .map(update)
+            .ok_or(MemoryError::Unknown)
+    }
+
+    fn delete_item(&mut self, on_topic: usize, on_item: &[usize]) -> Result<Item> {
         let mut ctx = self.lock_unwrap();
         let idxs = ctx.idxs.clone();
         idxs.get(on_topic)
             .and_then(|topic| ctx.data.get_mut(topic))
-            .map(|item| item.get(on_item))
-            .flatten()
+            .and_then(|items| item::remove(items, on_item))
             .ok_or(MemoryError::Unknown)
+    }
+
+    fn access_topic(&mut self, on_topic: usize) -> Result<Vec<Item>> {
+        let ctx = self.lock_unwrap();
+        ctx.idxs
+            .get(on_topic)
+            .and_then(|topic| ctx.data.get(topic))
             .cloned()
+            .ok_or(MemoryError::Unknown)
     }
 
-    fn delete_item(&mut self, on_topic: usize, on_item: usize) -> Result<V> {
+    fn update_topic(&mut self, on_topic: usize, update: impl FnOnce(&mut Vec<Item>)) -> Result<()> {
         let mut ctx = self.lock_unwrap();
         let idxs = ctx.idxs.clone();
         idxs.get(on_topic)
             .and_then(|topic| ctx.data.get_mut(topic))
-            .map(|item| item.remove(on_item)) // items displayed in order
+            .map(update)
+            .ok_or(MemoryError::Unknown)
+    }
+
+    fn indent_item(
+        &mut self,
+        on_topic: usize,
+        on_item: &[usize],
+        under: usize,
+    ) -> Result<Vec<usize>> {
+        let mut ctx = self.lock_unwrap();
+        let idxs = ctx.idxs.clone();
+        idxs.get(on_topic)
+            .and_then(|topic| ctx.data.get_mut(topic))
+            .and_then(|items| item::indent(items, on_item, under))
+            .ok_or(MemoryError::Unknown)
+    }
+
+    fn outdent_item(&mut self, on_topic: usize, on_item: &[usize]) -> Result<Vec<usize>> {
+        let mut ctx = self.lock_unwrap();
+        let idxs = ctx.idxs.clone();
+        idxs.get(on_topic)
+            .and_then(|topic| ctx.data.get_mut(topic))
+            .and_then(|items| item::outdent(items, on_item))
             .ok_or(MemoryError::Unknown)
     }
 
@@ -148,8 +643,9 @@ where
             true => Err(MemoryError::Unknown),
             _ => {
                 ctx.idxs.push(topic.clone());
+                ctx.created.insert(topic.clone(), Utc::now());
                 if ctx.data.insert(topic, vec![]).is_none() {
-                    return Ok(());
+                    Ok(())
                 } else {
                     Err(MemoryError::Unknown)
                 }
@@ -167,12 +663,136 @@ where
             .ok_or(MemoryError::Unknown);
         if res.is_ok() {
             //#[allow(mutable_borrow_reservation_conflict)]
-            idxs.remove(idxs.iter().position(|t| t == topic.unwrap()).unwrap());
+            let topic_id = idxs.remove(idxs.iter().position(|t| t == topic.unwrap()).unwrap());
+            ctx.views.remove(&topic_id);
+            ctx.done_since.remove(&topic_id);
+            ctx.created.remove(&topic_id);
             ctx.idxs = idxs;
         }
 
         res
     }
+
+    fn hide_done(&mut self, on_topic: usize) -> Result<bool> {
+        let mut ctx = self.lock_unwrap();
+        let topic = ctx
+            .idxs
+            .get(on_topic)
+            .cloned()
+            .ok_or(MemoryError::Unknown)?;
+        let view = ctx.views.entry(topic).or_default();
+        view.hide_done = !view.hide_done;
+        Ok(view.hide_done)
+    }
+
+    fn sort_items(&mut self, on_topic: usize) -> Result<Sort> {
+        let mut ctx = self.lock_unwrap();
+        let topic = ctx
+            .idxs
+            .get(on_topic)
+            .cloned()
+            .ok_or(MemoryError::Unknown)?;
+        let view = ctx.views.entry(topic).or_default();
+        view.sort = view.sort.next();
+        Ok(view.sort)
+    }
+
+    fn sort_topics(&mut self) -> TopicSort {
+        let mut ctx = self.lock_unwrap();
+        ctx.topic_sort = ctx.topic_sort.next();
+        ctx.topic_sort
+    }
+
+    fn remember_views(&mut self) -> bool {
+        let mut ctx = self.lock_unwrap();
+        ctx.remember_views = !ctx.remember_views;
+        ctx.remember_views
+    }
+
+    fn archive_topic(&mut self, on: usize) -> Result<()> {
+        let mut ctx = self.lock_unwrap();
+        ctx.archive_topic(on, Utc::now())
+            .ok_or(MemoryError::Unknown)
+    }
+
+    fn restore_topic(&mut self, on_archived: usize) -> Result<()> {
+        let mut ctx = self.lock_unwrap();
+        if on_archived >= ctx.archive.len() {
+            return Err(MemoryError::Unknown);
+        }
+        let Archived {
+            topic,
+            items,
+            created,
+            ..
+        } = ctx.archive.remove(on_archived);
+        // a topic of the same name might have been added in the meantime
+        match ctx.data.get_mut(&topic) {
+            Some(active) => active.extend(items),
+            None => {
+                ctx.idxs.push(topic.clone());
+                if let Some(created) = created {
+                    ctx.created.insert(topic.clone(), created);
+                }
+                ctx.data.insert(topic, items);
+            }
+        }
+        Ok(())
+    }
+
+    fn delete_archived(&mut self, on_archived: usize) -> Result<()> {
+        let mut ctx = self.lock_unwrap();
+        match on_archived < ctx.archive.len() {
+            true => {
+                ctx.archive.remove(on_archived);
+                Ok(())
+            }
+            _ => Err(MemoryError::Unknown),
+        }
+    }
+
+    fn set_auto_archive(&mut self, days: Option<u32>) {
+        self.lock_unwrap().auto_archive = days;
+    }
+
+    fn auto_archive(&mut self) -> usize {
+        self.lock_unwrap().settle(Utc::now())
+    }
+
+    /// Adds imported topics, items of topics which exist already are
+    /// appended to them. Returns the number of imported items.
+    fn import(&mut self, topics: Vec<(K, Vec<Item>)>) -> usize {
+        let mut ctx = self.lock_unwrap();
+        let now = Utc::now();
+        topics
+            .into_iter()
+            .map(|(topic, items)| {
+                let n = item::walk(&items).len();
+                match ctx.data.get_mut(&topic) {
+                    Some(active) => active.extend(items),
+                    None => {
+                        ctx.idxs.push(topic.clone());
+                        ctx.created.insert(topic.clone(), now);
+                        ctx.data.insert(topic, items);
+                    }
+                }
+                n
+            })
+            .sum()
+    }
+
+    /// The topic `on`, or all topics in their stored order.
+    fn export(&mut self, on: Option<usize>) -> Result<Vec<(K, Vec<Item>)>> {
+        let ctx = self.lock_unwrap();
+        let topics = match on {
+            Some(on) => vec![ctx.idxs.get(on).ok_or(MemoryError::Unknown)?],
+            None => ctx.idxs.iter().collect(),
+        };
+        Ok(topics
+            .into_iter()
+            .filter_map(|topic| Some((topic.clone(), ctx.data.get(topic)?.clone())))
+            .collect())
+    }
 }
 
 #[cfg(test)]
@@ -181,7 +801,148 @@ mod test {
 
     #[test]
     fn read() {
-        let mem = deserialize::<String, String>(crate::config::DATA);
+        let mem = deserialize::<String, Item>(crate::config::DATA);
         dbg!(mem);
     }
+
+    #[test]
+    fn hidden_done_items_are_folded() {
+        let mut mem = Memory::<String, Item>::new();
+        mem.idxs.push("t".to_owned());
+        mem.data.insert(
+            "t".to_owned(),
+            vec![Item::from("0a"), Item::from("1b"), Item::from("0c")],
+        );
+        assert_eq!(mem.view(0), vec![vec![0], vec![1], vec![2]]);
+        assert_eq!(mem.folded(0), 0);
+
+        mem.views.insert(
+            "t".to_owned(),
+            View {
+                hide_done: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(mem.view(0), vec![vec![0], vec![2]]);
+        assert_eq!(mem.folded(0), 1);
+    }
+
+    #[test]
+    fn sorting_keeps_manual_order() {
+        let mut mem = Memory::<String, Item>::new();
+        mem.idxs = vec!["b".to_owned(), "a".to_owned()];
+        mem.data.insert(
+            "b".to_owned(),
+            vec![Item::from("1z"), Item::from("0y"), Item::from("0x")],
+        );
+        mem.data.insert("a".to_owned(), vec![Item::from("1w")]);
+
+        mem.views.insert(
+            "b".to_owned(),
+            View {
+                sort: Sort::Status,
+                ..Default::default()
+            },
+        );
+        assert_eq!(mem.view(0), vec![vec![1], vec![2], vec![0]]);
+        mem.views.get_mut("b").unwrap().sort = Sort::Alphabetical;
+        assert_eq!(mem.view(0), vec![vec![2], vec![1], vec![0]]);
+
+        mem.topic_sort = TopicSort::Alphabetical;
+        assert_eq!(mem.topic_view(), vec![1, 0]);
+        mem.topic_sort = TopicSort::Ratio;
+        assert_eq!(mem.topic_view(), vec![0, 1]);
+
+        assert_eq!(mem.idxs, vec!["b".to_owned(), "a".to_owned()]);
+        assert_eq!(mem.data["b"][0].entry, "1z");
+    }
+
+    #[test]
+    fn subtasks_roll_up() {
+        let mut parent = Item::from("0a");
+        parent.children = vec![Item::from("1b"), Item::from("0c")];
+        parent.children[1].children = vec![Item::from("1d"), Item::from("0e")];
+        let mut mem = Memory::<String, Item>::new();
+        mem.idxs.push("t".to_owned());
+        mem.data
+            .insert("t".to_owned(), vec![parent, Item::from("1f")]);
+
+        // (1 + (1 + 0) / 2) / 2 for the parent, 1 for the other item
+        assert_eq!(ratio(&mem.data["t"]), 0.875);
+        assert_eq!(
+            mem.view(0),
+            vec![
+                vec![0],
+                vec![0, 0],
+                vec![0, 1],
+                vec![0, 1, 0],
+                vec![0, 1, 1],
+                vec![1]
+            ]
+        );
+
+        mem.data.get_mut("t").unwrap()[0].children[1].collapsed = true;
+        mem.views.insert(
+            "t".to_owned(),
+            View {
+                hide_done: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(mem.view(0), vec![vec![0], vec![0, 1]]);
+        assert_eq!(mem.folded(0), 2);
+    }
+
+    #[test]
+    fn tagged_items_across_topics() {
+        let mut mem = Memory::<String, Item>::new();
+        mem.idxs = vec!["a".to_owned(), "b".to_owned()];
+        let mut parent = Item::from("0x");
+        parent.children = vec![Item::from("0y #urgent")];
+        mem.data.insert("a".to_owned(), vec![parent]);
+        mem.data.insert(
+            "b".to_owned(),
+            vec![Item::from("1z"), Item::from("1#Urgent")],
+        );
+        assert_eq!(mem.tagged("urgent"), vec![(0, vec![0, 0]), (1, vec![1])]);
+    }
+
+    #[test]
+    fn done_topics_are_auto_archived() {
+        let mut mem = Memory::<String, Item>::new();
+        mem.idxs = vec!["done".to_owned(), "open".to_owned()];
+        mem.data.insert("done".to_owned(), vec![Item::from("1a")]);
+        mem.data.insert("open".to_owned(), vec![Item::from("0b")]);
+        mem.auto_archive = Some(3);
+
+        let then = Utc::now();
+        assert_eq!(mem.settle(then), 0);
+        assert_eq!(mem.settle(then + Duration::days(2)), 0);
+        assert_eq!(mem.settle(then + Duration::days(3)), 1);
+
+        assert_eq!(mem.idxs, vec!["open".to_owned()]);
+        assert_eq!(mem.archive[0].topic, "done");
+        assert_eq!(mem.archive[0].archived, then + Duration::days(3));
+        assert!(mem.done_since.is_empty());
+    }
+
+    #[test]
+    fn auto_archive_counts_from_last_completion() {
+        let mut mem = Memory::<String, Item>::new();
+        let then = Utc::now();
+        let done = |text, days| {
+            let mut item = Item::new(text, then);
+            item.toggle(then + Duration::days(days));
+            item
+        };
+        mem.idxs = vec!["done".to_owned()];
+        mem.data
+            .insert("done".to_owned(), vec![done("a", 1), done("b", 5)]);
+        mem.auto_archive = Some(3);
+
+        // never seen open, yet not archived before three days since `b`
+        assert_eq!(mem.settle(then + Duration::days(7)), 0);
+        assert!(mem.done_since.is_empty());
+        assert_eq!(mem.settle(then + Duration::days(8)), 1);
+    }
 }
@@ -0,0 +1,361 @@
+//! Org-mode outlines. Headlines without a keyword are topics, `TODO` and
+//! `DONE` headlines below them are items, deeper ones subtasks:
+//!
+//! ```text
+//! * work
+//! ** TODO [#A] report #q3 :q3:
+//!    DEADLINE: <2026-10-20 Tue +1w>
+//!    :PROPERTIES:
+//!    :CREATED:  [2026-10-01 Thu 09:30]
+//!    :END:
+//!    notes on the report
+//! *** DONE figures
+//!     CLOSED: [2026-10-18 Sun 12:00]
+//! ```
+//!
+//! Timestamps are in local time, to the minute. `SCHEDULED` dates are kept,
+//! and count as due dates when there is no `DEADLINE`.
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+
+use crate::date::{self, Recurrence};
+use crate::item::{Item, Priority};
+use crate::markdown;
+
+fn stamp(at: DateTime<Utc>) -> String {
+    at.with_timezone(&Local)
+        .format("[%Y-%m-%d %a %H:%M]")
+        .to_string()
+}
+
+fn repeater(recurrence: Recurrence) -> String {
+    match recurrence {
+        Recurrence::Daily => "+1d".to_owned(),
+        Recurrence::Weekly(_) => "+1w".to_owned(),
+        Recurrence::Monthly => "+1m".to_owned(),
+        Recurrence::Every(days) => format!("+{days}d"),
+    }
+}
+
+fn write_entries(items: &[Item], level: usize, s: &mut String) {
+    let indent = " ".repeat(level + 1);
+    items.iter().for_each(|item| {
+        let keyword = match item.is_done() {
+            true => "DONE",
+            _ => "TODO",
+        };
+        let priority = item
+            .priority
+            .map(|priority| format!(" [#{priority}]"))
+            .unwrap_or_default();
+        let tags = match item.tags().is_empty() {
+            true => String::new(),
+            _ => format!(" :{}:", item.tags().join(":")),
+        };
+        s.push_str(&format!(
+            "{} {keyword}{priority} {}{tags}\n",
+            "*".repeat(level),
+            item.text()
+        ));
+
+        let mut planning = vec![];
+        planning.extend(item.completed.map(|at| format!("CLOSED: {}", stamp(at))));
+        let repeater = item
+            .recurrence
+            .map(|recurrence| format!(" {}", repeater(recurrence)))
+            .unwrap_or_default();
+        // a due date taken from the schedule is not a deadline of its own
+        let (scheduled, due) = match item.scheduled == item.due {
+            true => (item.scheduled.map(|at| (at, repeater.as_str())), None),
+            _ => (item.scheduled.map(|at| (at, "")), item.due),
+        };
+        planning.extend(
+            scheduled.map(|(at, repeater)| {
+                format!("SCHEDULED: <{}{repeater}>", at.format("%Y-%m-%d %a"))
+            }),
+        );
+        planning
+            .extend(due.map(|due| format!("DEADLINE: <{}{repeater}>", due.format("%Y-%m-%d %a"))));
+        if !planning.is_empty() {
+            s.push_str(&format!("{indent}{}\n", planning.join(" ")));
+        }
+
+        let properties = [("CREATED", item.created), ("MODIFIED", item.modified)]
+            .into_iter()
+            .filter_map(|(name, at)| Some(format!("{indent}:{name}: {}\n", stamp(at?))))
+            .collect::<String>();
+        if !properties.is_empty() {
+            s.push_str(&format!(
+                "{indent}:PROPERTIES:\n{properties}{indent}:END:\n"
+            ));
+        }
+
+        item.notes.lines().for_each(|line| match line.is_empty() {
+            true => s.push('\n'),
+            _ => s.push_str(&format!("{indent}{line}\n")),
+        });
+        write_entries(&item.children, level + 1, s);
+    });
+}
+
+/// Topics as first level headlines, their items below them.
+pub fn export(topics: &[(String, Vec<Item>)]) -> String {
+    let mut s = String::new();
+    topics.iter().for_each(|(topic, items)| {
+        s.push_str(&format!("* {topic}\n"));
+        write_entries(items, 2, &mut s);
+    });
+    s
+}
+
+/// The level of a headline and its title.
+fn headline(line: &str) -> Option<(usize, &str)> {
+    let title = line.trim_start_matches('*');
+    let level = line.len() - title.len();
+    match level > 0 && title.starts_with(' ') {
+        true => Some((level, title.trim())),
+        _ => None,
+    }
+}
+
+/// A timestamp at the start of `s`, with the recurrence of its repeater.
+fn timestamp(s: &str) -> Option<(NaiveDateTime, Option<Recurrence>)> {
+    let s = s.trim_start().strip_prefix(['<', '['])?;
+    let s = &s[..s.find(['>', ']'])?];
+    let mut words = s.split_whitespace();
+    let date = NaiveDate::parse_from_str(words.next()?, "%Y-%m-%d").ok()?;
+    let mut time = NaiveTime::MIN;
+    let mut recurrence = None;
+    words.for_each(|word| {
+        if let Ok(t) = NaiveTime::parse_from_str(word, "%H:%M") {
+            time = t;
+        } else if word.starts_with(['.', '+']) {
+            recurrence = match word.trim_start_matches(['.', '+']) {
+                "1d" => Some(Recurrence::Daily),
+                "1w" => Some(Recurrence::Weekly(date.weekday())),
+                "1m" => Some(Recurrence::Monthly),
+                every => date::offset_days(every)
+                    .and_then(|days| u32::try_from(days).ok())
+                    .filter(|days| *days > 0)
+                    .map(Recurrence::Every),
+            };
+        }
+    });
+    Some((date.and_time(time), recurrence))
+}
+
+fn local(at: NaiveDateTime) -> Option<DateTime<Utc>> {
+    Local
+        .from_local_datetime(&at)
+        .earliest()
+        .map(|at| at.with_timezone(&Utc))
+}
+
+/// An entry as it is being read: its depth, item and body lines.
+type Entry = (usize, Item, Vec<String>);
+
+fn finish((depth, mut item, body): Entry) -> (usize, Item) {
+    let mut notes = vec![];
+    let mut drawer = false;
+    let mut scheduled = None;
+    body.iter().for_each(|line| {
+        let trimmed = line.trim();
+        match (drawer, trimmed) {
+            (false, ":PROPERTIES:") => drawer = true,
+            (true, ":END:") => drawer = false,
+            (true, _) => {
+                let (name, value) = trimmed
+                    .trim_start_matches(':')
+                    .split_once(':')
+                    .unwrap_or_default();
+                let at = timestamp(value).and_then(|(at, _)| local(at));
+                match name.to_uppercase().as_str() {
+                    "CREATED" => item.created = at,
+                    "MODIFIED" => item.modified = at,
+                    _ => (),
+                }
+            }
+            _ if ["SCHEDULED:", "DEADLINE:", "CLOSED:"]
+                .iter()
+                .any(|keyword| trimmed.starts_with(keyword)) =>
+            {
+                let stamp_after = |keyword: &str| {
+                    trimmed
+                        .find(keyword)
+                        .and_then(|i| timestamp(&trimmed[i + keyword.len()..]))
+                };
+                if let Some((at, recurrence)) = stamp_after("DEADLINE:") {
+                    item.due = Some(at.date());
+                    item.recurrence = recurrence;
+                }
+                scheduled = stamp_after("SCHEDULED:").or(scheduled);
+                if let Some((at, _)) = stamp_after("CLOSED:") {
+                    item.completed = local(at);
+                }
+            }
+            _ => notes.push(line.as_str()),
+        }
+    });
+    if let Some((at, recurrence)) = scheduled {
+        item.scheduled = Some(at.date());
+        if item.due.is_none() {
+            item.due = item.scheduled;
+            item.recurrence = recurrence;
+        }
+    }
+
+    let indent = notes
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or_default();
+    item.notes = notes
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or_default().trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_matches('\n')
+        .to_owned();
+    (depth, item)
+}
+
+/// An item of a `TODO` or `DONE` headline.
+fn entry(title: &str, at: DateTime<Utc>) -> Option<Item> {
+    let (keyword, title) = title.split_once(' ').unwrap_or((title, ""));
+    let done = match keyword {
+        "TODO" => false,
+        "DONE" => true,
+        _ => return None,
+    };
+    let mut title = title.trim();
+    let mut priority = None;
+    if let Some(rest) = title.strip_prefix("[#") {
+        priority = rest.chars().next().and_then(|c| Priority::try_from(c).ok());
+        title = rest.get(2..).unwrap_or_default().trim_start();
+    }
+    let mut words = title.split_whitespace().collect::<Vec<_>>();
+    let tags = match words.last() {
+        Some(last) if last.len() > 2 && last.starts_with(':') && last.ends_with(':') => {
+            words.pop().unwrap_or_default()
+        }
+        _ => "",
+    };
+
+    let mut item = Item::new("", at);
+    // the text is taken as is, not for any `due:` it might contain
+    item.entry = format!("{}{}", done as u8, words.join(" "));
+    item.priority = priority;
+    item.completed = done.then_some(at);
+    let tags = tags
+        .split(':')
+        .filter(|tag| !tag.is_empty() && !item.has_tag(tag))
+        .map(|tag| format!(" #{tag}"))
+        .collect::<String>();
+    item.entry.push_str(&tags);
+    Some(item)
+}
+
+/// Headlines with `TODO` or `DONE` become items of the topic of the first
+/// level headline above them, or of `topic` if there is none or they are
+/// first level themselves. Other headlines are topics, or skipped below the
+/// first level.
+pub fn import(s: &str, topic: &str, at: DateTime<Utc>) -> Vec<(String, Vec<Item>)> {
+    let mut topics: Vec<(String, Vec<(usize, Item)>)> = vec![];
+    // the entry being read and the topic it goes to
+    let mut current: Option<(usize, Entry)> = None;
+    let mut on = None;
+
+    for line in s.lines() {
+        let Some((level, title)) = headline(line) else {
+            if let Some((_, (_, _, body))) = current.as_mut() {
+                body.push(line.to_owned());
+            }
+            continue;
+        };
+        if let Some((on, entry)) = current.take() {
+            topics[on].1.push(finish(entry));
+        }
+        match (entry(title, at), on) {
+            (Some(item), Some(on)) if level > 1 => current = Some((on, (level - 2, item, vec![]))),
+            (Some(item), _) => {
+                let on = match topics.iter().position(|(name, _)| name == topic) {
+                    Some(on) => on,
+                    None => {
+                        topics.push((topic.to_owned(), vec![]));
+                        topics.len() - 1
+                    }
+                };
+                current = Some((on, (level.saturating_sub(2), item, vec![])));
+            }
+            (None, _) if level == 1 => {
+                topics.push((title.to_owned(), vec![]));
+                on = Some(topics.len() - 1);
+            }
+            _ => (),
+        }
+    }
+    if let Some((on, entry)) = current.take() {
+        topics[on].1.push(finish(entry));
+    }
+
+    topics
+        .into_iter()
+        .map(|(topic, entries)| (topic, markdown::nest(entries)))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn outlines() {
+        let at = Local
+            .with_ymd_and_hms(2026, 10, 18, 12, 34, 0)
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut a = Item::new("report #q3 due:2026-10-20 rec:3d", at);
+        a.priority = Priority::try_from('A').ok();
+        a.notes = "first\n\n  indented".to_owned();
+        let mut b = Item::new("figures", at);
+        b.toggle(at + chrono::Duration::hours(1));
+        a.children = vec![b];
+        let mut slides = Item::new("slides due:2026-10-22 rec:daily", at);
+        slides.scheduled = slides.due;
+        let mut talk = Item::new("talk due:2026-10-23", at);
+        talk.scheduled = NaiveDate::from_ymd_opt(2026, 10, 21);
+        let topics = vec![("work".to_owned(), vec![a, slides, talk])];
+
+        let s = export(&topics);
+        assert!(s.starts_with(
+            "* work\n** TODO [#A] report #q3 :q3:\n   DEADLINE: <2026-10-20 Tue +3d>\n"
+        ));
+        assert!(s.contains("\n   first\n\n     indented\n*** DONE figures\n    CLOSED: ["));
+        assert!(s.contains("** TODO slides\n   SCHEDULED: <2026-10-22 Thu +1d>\n"));
+        assert!(
+            s.contains("** TODO talk\n   SCHEDULED: <2026-10-21 Wed> DEADLINE: <2026-10-23 Fri>\n")
+        );
+        assert_eq!(import(&s, "file", at), topics);
+    }
+
+    #[test]
+    fn foreign_outlines() {
+        let at = Utc::now();
+        let s = "#+TITLE: mine\n* TODO loose :home:\n  SCHEDULED: <2026-10-21 Wed>\n\
+                 * Projects\n** Garden\n*** TODO dig\n    CLOSED: [2026-10-18 Sun]\n\
+                 ** DONE [#B] plant\n   some notes\n";
+        let topics = import(s, "file", at);
+        assert_eq!(topics[0].0, "file");
+        assert_eq!(topics[0].1[0].text(), "loose #home");
+        assert_eq!(topics[0].1[0].due, NaiveDate::from_ymd_opt(2026, 10, 21));
+        assert_eq!(topics[0].1[0].scheduled, topics[0].1[0].due);
+        assert_eq!(topics[1].0, "Projects");
+        let items = &topics[1].1;
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].text(), "dig");
+        assert!(!items[0].is_done());
+        assert!(items[1].is_done());
+        assert_eq!(items[1].notes, "some notes");
+        assert_eq!(items[1].priority, Priority::try_from('B').ok());
+    }
+}
@@ -0,0 +1,219 @@
+//! A report of all topics as a single HTML page, without anything it would
+//! have to load: progress of each topic, overdue items, recent completions
+//! and the items themselves.
+use chrono::{DateTime, Duration, Local, Utc};
+
+use crate::item::{self, Item};
+use crate::memory;
+
+/// How far back completions count as recent.
+const RECENT_DAYS: i64 = 7;
+
+const STYLE: &str =
+    "body { font-family: sans-serif; max-width: 50em; margin: 2em auto; color: #222; }
+h1 { margin-bottom: 0; }
+.meta { color: #777; }
+table { border-collapse: collapse; width: 100%; }
+td, th { padding: .3em .6em; text-align: left; }
+td.n { text-align: right; }
+.bar { background: #eee; width: 12em; height: .8em; }
+.bar div { background: #2a9d8f; height: 100%; }
+ul.items { list-style: none; padding-left: 1.2em; }
+.done { color: #999; text-decoration: line-through; }
+.overdue { color: #c0392b; }
+.tag { color: #b58900; }
+.notes { color: #777; white-space: pre-wrap; margin: 0 0 .3em 1.5em; }";
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The text of an item with its tags marked.
+fn text(item: &Item) -> String {
+    item.text()
+        .split(' ')
+        .map(|word| match item::is_tag(word) {
+            true => format!("<span class=\"tag\">{}</span>", escape(word)),
+            _ => escape(word),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A bar of the ratio shown on the topics screen, along with the ratio.
+fn progress(items: &[Item]) -> String {
+    let q = memory::ratio(items);
+    let width = match q.is_nan() {
+        true => 0.0,
+        _ => q * 100.0,
+    };
+    format!(
+        "<td><div class=\"bar\"><div style=\"width: {width:.0}%\"></div></div></td><td class=\"n\">[{q:.2}]</td>"
+    )
+}
+
+fn write_items(items: &[Item], now: DateTime<Utc>, s: &mut String) {
+    let today = now.with_timezone(&Local).date_naive();
+    s.push_str("<ul class=\"items\">\n");
+    items.iter().for_each(|item| {
+        let (checkbox, class) = match (item.is_done(), item.is_overdue(today)) {
+            (true, _) => ("&#9745;", " class=\"done\""),
+            (_, true) => ("&#9744;", " class=\"overdue\""),
+            _ => ("&#9744;", ""),
+        };
+        let priority = item
+            .priority
+            .map(|priority| format!("<b>{priority}</b> "))
+            .unwrap_or_default();
+        let due = item
+            .due
+            .map(|due| format!(" <small>due {due}</small>"))
+            .unwrap_or_default();
+        s.push_str(&format!(
+            "<li>{checkbox} {priority}<span{class}>{}</span>{due}",
+            text(item)
+        ));
+        if !item.notes.is_empty() {
+            s.push_str(&format!(
+                "<div class=\"notes\">{}</div>",
+                escape(&item.notes)
+            ));
+        }
+        if !item.children.is_empty() {
+            write_items(&item.children, now, s);
+        }
+        s.push_str("</li>\n");
+    });
+    s.push_str("</ul>\n");
+}
+
+pub fn html(topics: &[(String, Vec<Item>)], now: DateTime<Utc>) -> String {
+    let today = now.with_timezone(&Local).date_naive();
+    let mut s = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>go22dos</title>\n\
+         <style>\n{STYLE}\n</style>\n</head>\n<body>\n<h1>go22dos</h1>\n<p class=\"meta\">{}</p>\n",
+        now.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+    );
+
+    s.push_str("<h2>topics</h2>\n<table>\n<tr><th>topic</th><th>progress</th><th></th><th>open</th><th>done</th></tr>\n");
+    topics.iter().for_each(|(topic, items)| {
+        let (done, open): (Vec<&Item>, Vec<&Item>) =
+            item::walk(items).into_iter().partition(|item| item.is_done());
+        s.push_str(&format!(
+            "<tr><td><a href=\"#{}\">{}</a></td>{}<td class=\"n\">{}</td><td class=\"n\">{}</td></tr>\n",
+            escape(topic),
+            escape(topic),
+            progress(items),
+            open.len(),
+            done.len()
+        ));
+    });
+    s.push_str("</table>\n");
+
+    let mut overdue = topics
+        .iter()
+        .flat_map(|(topic, items)| {
+            item::walk(items)
+                .into_iter()
+                .filter(|item| item.is_overdue(today))
+                .map(move |item| (topic, item))
+        })
+        .collect::<Vec<_>>();
+    overdue.sort_by_key(|(_, item)| item.due);
+    s.push_str("<h2>overdue</h2>\n");
+    match overdue.is_empty() {
+        true => s.push_str("<p>nothing overdue</p>\n"),
+        _ => {
+            s.push_str("<ul>\n");
+            overdue.iter().for_each(|(topic, item)| {
+                s.push_str(&format!(
+                    "<li class=\"overdue\">{}: {} <small>due {}</small></li>\n",
+                    escape(topic),
+                    text(item),
+                    item.due.unwrap_or(today)
+                ))
+            });
+            s.push_str("</ul>\n");
+        }
+    }
+
+    // recurring items keep their completions, others the last one
+    let since = now - Duration::days(RECENT_DAYS);
+    let mut recent = topics
+        .iter()
+        .flat_map(move |(topic, items)| {
+            item::walk(items).into_iter().flat_map(move |item| {
+                item.completions
+                    .iter()
+                    .copied()
+                    .chain(item.completed)
+                    .filter(move |at| *at >= since)
+                    .map(move |at| (at, topic, item))
+            })
+        })
+        .collect::<Vec<_>>();
+    recent.sort_by_key(|(at, _, _)| std::cmp::Reverse(*at));
+    s.push_str(&format!("<h2>done in the last {RECENT_DAYS} days</h2>\n"));
+    match recent.is_empty() {
+        true => s.push_str("<p>nothing done</p>\n"),
+        _ => {
+            s.push_str("<ul>\n");
+            recent.iter().for_each(|(at, topic, item)| {
+                s.push_str(&format!(
+                    "<li>{} {}: {}</li>\n",
+                    at.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+                    escape(topic),
+                    text(item)
+                ))
+            });
+            s.push_str("</ul>\n");
+        }
+    }
+
+    topics.iter().for_each(|(topic, items)| {
+        s.push_str(&format!(
+            "<h2 id=\"{}\">{}</h2>\n",
+            escape(topic),
+            escape(topic)
+        ));
+        match items.is_empty() {
+            true => s.push_str("<p>no items</p>\n"),
+            _ => write_items(items, now, &mut s),
+        }
+    });
+
+    s.push_str("</body>\n</html>\n");
+    s
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn report() {
+        let now = Utc::now();
+        let mut done = Item::new("shipped <it>", now - Duration::days(2));
+        done.toggle(now - Duration::days(1));
+        let mut old = Item::new("long ago", now - Duration::days(30));
+        old.toggle(now - Duration::days(20));
+        let late = Item::new("late #urgent due:2020-01-01", now);
+        let topics = vec![
+            ("work".to_owned(), vec![done, old, late]),
+            ("empty".to_owned(), vec![]),
+        ];
+
+        let s = html(&topics, now);
+        assert!(!s.contains("<script") && !s.contains("src=") && !s.contains("<link"));
+        assert!(
+            s.contains("<td class=\"n\">[0.67]</td><td class=\"n\">1</td><td class=\"n\">2</td>")
+        );
+        assert!(s.contains("style=\"width: 0%\"></div></div></td><td class=\"n\">[NaN]"));
+        assert!(s.contains("<li class=\"overdue\">work: late <span class=\"tag\">#urgent</span> <small>due 2020-01-01</small></li>"));
+        assert!(s.contains("work: shipped &lt;it&gt;</li>"));
+        assert!(!s.contains("work: long ago</li>"));
+    }
+}
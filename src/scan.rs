@@ -0,0 +1,305 @@
+//! `TODO`, `FIXME` and `XXX` comments in source files, kept in a topic as
+//! items such as `FIXME handle errors src/main.rs:42`.
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+
+use crate::item::Item;
+
+pub const KEYWORDS: [&str; 3] = ["TODO", "FIXME", "XXX"];
+
+/// Where comments may start, anything before a keyword has to contain one.
+const LEADERS: [&str; 6] = ["//", "#", "/*", "--", ";", "<!--"];
+
+/// What is kept next to a store, named after it.
+const COMPANIONS: [&str; 6] = ["", "-wal", "-shm", "-journal", ".lock", ".tmp"];
+
+/// Directories which are not worth looking into.
+const SKIPPED: [&str; 3] = ["target", "node_modules", "vendor"];
+
+/// Files larger than this are not source files.
+const MAX_LEN: u64 = 1 << 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    pub keyword: String,
+    pub text: String,
+    pub file: String,
+    pub line: usize,
+}
+
+impl Comment {
+    /// The text of its item.
+    pub fn entry(&self) -> String {
+        [
+            self.keyword.as_str(),
+            self.text.as_str(),
+            &format!("{}:{}", self.file, self.line),
+        ]
+        .into_iter()
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+    }
+
+    /// The comment of an item, if it was made from one.
+    pub fn of(item: &Item) -> Option<Self> {
+        let text = item.text();
+        let (keyword, rest) = text.split_once(' ')?;
+        KEYWORDS.contains(&keyword).then_some(())?;
+        let (text, location) = rest.rsplit_once(' ').unwrap_or(("", rest));
+        let (file, line) = location.rsplit_once(':')?;
+        Some(Self {
+            keyword: keyword.to_owned(),
+            text: text.to_owned(),
+            file: file.to_owned(),
+            line: line.parse().ok()?,
+        })
+    }
+
+    /// Comments are the same wherever they moved to in their file.
+    fn is(&self, other: &Self) -> bool {
+        (&self.keyword, &self.text, &self.file) == (&other.keyword, &other.text, &other.file)
+    }
+}
+
+/// The keyword starting at `i` of `line`, if it is a word of its own.
+fn keyword_at(line: &str, i: usize) -> Option<&'static str> {
+    let word = |c: char| c.is_alphanumeric() || c == '_';
+    let before = line[..i].chars().next_back();
+    KEYWORDS.into_iter().find(|keyword| {
+        line[i..].starts_with(keyword)
+            && !before.is_some_and(word)
+            && !line[i + keyword.len()..].chars().next().is_some_and(word)
+    })
+}
+
+/// Whether a comment starts in `before`, by a leader at the start of a word
+/// and outside of any string.
+fn commented(before: &str) -> bool {
+    LEADERS.iter().any(|leader| {
+        before.match_indices(leader).any(|(j, _)| {
+            let start = &before[..j];
+            (start.is_empty() || start.ends_with(char::is_whitespace))
+                && start.matches('"').count().is_multiple_of(2)
+        })
+    })
+}
+
+pub fn comments(s: &str, file: &str) -> Vec<Comment> {
+    s.lines()
+        .enumerate()
+        .filter_map(|(n, line)| {
+            let (i, keyword) = line.char_indices().find_map(|(i, _)| {
+                let keyword = keyword_at(line, i)?;
+                commented(&line[..i]).then_some((i, keyword))
+            })?;
+
+            let mut text = line[i + keyword.len()..].trim_start();
+            // an owner, as in `TODO(someone):`
+            if let Some(rest) = text.strip_prefix('(') {
+                text = rest.split_once(')').map_or(text, |(_, rest)| rest);
+            }
+            let text = text
+                .trim_start_matches([':', '-', ' '])
+                .trim_end()
+                .trim_end_matches("*/")
+                .trim_end_matches("-->")
+                .trim();
+            Some(Comment {
+                keyword: keyword.to_owned(),
+                text: text.split_whitespace().collect::<Vec<_>>().join(" "),
+                file: file.to_owned(),
+                line: n + 1,
+            })
+        })
+        .collect()
+}
+
+/// The files of the store at `store`, which has todos of its own: the store
+/// itself and the ones kept next to it.
+fn store_files(store: &Path) -> Vec<PathBuf> {
+    let dir = match store.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let (Ok(dir), Some(name)) = (fs::canonicalize(dir), store.file_name()) else {
+        return vec![];
+    };
+    COMPANIONS
+        .iter()
+        .map(|suffix| {
+            let mut name = name.to_owned();
+            name.push(suffix);
+            dir.join(name)
+        })
+        .collect()
+}
+
+/// Comments of all files below `root`, skipping hidden and build directories,
+/// files which are not text and the files of the store at `store`.
+pub fn walk(root: &Path, store: Option<&Path>) -> io::Result<Vec<Comment>> {
+    fn visit(
+        root: &Path,
+        dir: &Path,
+        skipped: &[PathBuf],
+        found: &mut Vec<Comment>,
+    ) -> io::Result<()> {
+        let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let path = entry.path();
+            let kind = entry.file_type()?;
+            // stores have todos of their own
+            if name.starts_with('.') || name.ends_with(".go22dos") || skipped.contains(&path) {
+                continue;
+            }
+            if kind.is_dir() && !SKIPPED.contains(&name.as_str()) {
+                visit(root, &path, skipped, found)?;
+            } else if kind.is_file() && entry.metadata()?.len() <= MAX_LEN {
+                if let Ok(s) = fs::read_to_string(&path) {
+                    let file = path.strip_prefix(root).unwrap_or(&path);
+                    found.extend(comments(&s, &file.to_string_lossy()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    let root = fs::canonicalize(root)?;
+    let skipped = store.map(store_files).unwrap_or_default();
+    let mut found = vec![];
+    visit(&root, &root, &skipped, &mut found)?;
+    Ok(found)
+}
+
+/// Brings the items made from comments in line with `comments`: new ones are
+/// added, moved ones follow them, ones which are gone are ticked off and ones
+/// which came back are reopened. Returns how many were added and ticked off.
+pub fn sync(items: &mut Vec<Item>, comments: &[Comment], at: DateTime<Utc>) -> (usize, usize) {
+    let mut seen = vec![false; items.len()];
+    let mut added = 0;
+    comments.iter().for_each(|comment| {
+        let found = items
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !seen[*i])
+            .filter(|(_, item)| Comment::of(item).is_some_and(|c| c.is(comment)))
+            // an open one, else the one done last
+            .max_by_key(|(_, item)| (!item.is_done(), item.completed));
+        match found.map(|(i, _)| i) {
+            Some(i) => {
+                seen[i] = true;
+                let item = &mut items[i];
+                if item.text() != comment.entry() {
+                    item.entry = format!("{}{}", &item.entry[..1], comment.entry());
+                    item.modified = Some(at);
+                }
+                if item.is_done() {
+                    item.toggle(at);
+                }
+            }
+            None => {
+                let mut item = Item::new("", at);
+                // the text is taken as is, not for any `due:` it might contain
+                item.entry = format!("0{}", comment.entry());
+                items.push(item);
+                seen.push(true);
+                added += 1;
+            }
+        }
+    });
+
+    let mut done = 0;
+    items.iter_mut().zip(seen).for_each(|(item, seen)| {
+        if !seen && !item.is_done() && Comment::of(item).is_some() {
+            item.toggle(at);
+            done += 1;
+        }
+    });
+    (added, done)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn comments_in_source() {
+        let s = "fn main() {\n    // TODO(me): split this up\n    let todo = \"TODO\"; // XXX: rename\n\
+                 # FIXME - handle errors\n/* XXX */\n<!-- TODO: docs -->\n    // TODOS are fine\n\
+                 let url = \"http://example.com/TODO\";\nlet s = \"a # TODO\";\nx = 1 # FIXME \"quoted\"\n";
+        let found = comments(s, "src/main.rs")
+            .into_iter()
+            .map(|comment| comment.entry())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                "TODO split this up src/main.rs:2",
+                "XXX rename src/main.rs:3",
+                "FIXME handle errors src/main.rs:4",
+                "XXX src/main.rs:5",
+                "TODO docs src/main.rs:6",
+                "FIXME \"quoted\" src/main.rs:10",
+            ]
+        );
+    }
+
+    #[test]
+    fn stores_skipped() {
+        let dir = std::env::temp_dir().join(format!("go22dos-scan-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        ["a.rs", "todos.db", "todos.db-wal", "todos.db.lock"]
+            .iter()
+            .for_each(|name| fs::write(dir.join(name), "// TODO x\n").unwrap());
+        let files = |store: Option<&Path>| {
+            walk(&dir, store)
+                .unwrap()
+                .into_iter()
+                .map(|comment| comment.file)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(files(None).len(), 4);
+        assert_eq!(files(Some(&dir.join("todos.db"))), vec!["a.rs"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn synced() {
+        let then = Utc::now();
+        let s = "// TODO one\n// FIXME two\n";
+        let mut items = vec![Item::new("by hand", then)];
+        assert_eq!(sync(&mut items, &comments(s, "a.rs"), then), (2, 0));
+        let notes = "on two";
+        items[2].notes = notes.to_owned();
+
+        // two moves down, one is gone, three is new
+        let now = then + chrono::Duration::hours(1);
+        let s = "// FIXME three\n\n// FIXME two\n";
+        assert_eq!(sync(&mut items, &comments(s, "a.rs"), now), (1, 1));
+        let texts = items.iter().map(Item::text).collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            vec![
+                "by hand",
+                "TODO one a.rs:1",
+                "FIXME two a.rs:3",
+                "FIXME three a.rs:1"
+            ]
+        );
+        assert!(!items[0].is_done());
+        assert!(items[1].is_done());
+        assert_eq!(items[2].notes, notes);
+
+        // one is back
+        assert_eq!(
+            sync(&mut items, &comments("// TODO one\n", "a.rs"), now),
+            (0, 2)
+        );
+        assert!(!items[1].is_done());
+    }
+}
@@ -0,0 +1,493 @@
+//! A store as an SQLite database, for stores too large to be rewritten as a
+//! whole and for several go22dos using the same store at once.
+//!
+//! Topics, archived ones included, and items are rows of their own; the tags
+//! of an item and the completions of recurring ones are kept alongside so
+//! they can be queried. Each save is a single transaction which only rewrites
+//! the topics that changed since they were loaded, so changes are saved as
+//! soon as they are made rather than once go22dos quits.
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::backend::Backend;
+use crate::item::{Item, Priority};
+use crate::memory::{Archived, Memory, MemoryError, View};
+use crate::watch::Watch;
+
+type Result<T> = std::result::Result<T, MemoryError>;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS topics (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    created TEXT,
+    done_since TEXT,
+    hide_done INTEGER NOT NULL DEFAULT 0,
+    sort TEXT NOT NULL DEFAULT 'manual',
+    archived TEXT
+);
+CREATE TABLE IF NOT EXISTS items (
+    id INTEGER PRIMARY KEY,
+    topic INTEGER NOT NULL REFERENCES topics(id) ON DELETE CASCADE,
+    parent INTEGER REFERENCES items(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    done INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    created TEXT,
+    modified TEXT,
+    completed TEXT,
+    due TEXT,
+    scheduled TEXT,
+    priority TEXT,
+    recurrence TEXT,
+    notes TEXT NOT NULL DEFAULT '',
+    collapsed INTEGER NOT NULL DEFAULT 0
+);
+CREATE UNIQUE INDEX IF NOT EXISTS topics_name ON topics(name) WHERE archived IS NULL;
+CREATE INDEX IF NOT EXISTS items_topic ON items(topic);
+CREATE TABLE IF NOT EXISTS tags (
+    item INTEGER NOT NULL REFERENCES items(id) ON DELETE CASCADE,
+    tag TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS tags_tag ON tags(tag);
+CREATE TABLE IF NOT EXISTS history (
+    item INTEGER NOT NULL REFERENCES items(id) ON DELETE CASCADE,
+    completed TEXT NOT NULL
+);";
+
+/// How long to wait for another go22dos writing to the same store.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The name a unit variant is serialized to, e.g. `manual`.
+fn name(value: impl Serialize) -> Result<String> {
+    match serde_json::to_value(value)? {
+        serde_json::Value::String(s) => Ok(s),
+        value => Ok(value.to_string()),
+    }
+}
+
+fn named<T: DeserializeOwned>(s: &str) -> Result<T> {
+    serde_json::from_value(serde_json::Value::String(s.to_owned())).map_err(MemoryError::JsonError)
+}
+
+pub struct Sqlite {
+    path: String,
+    conn: Connection,
+    /// Ids and items of the topics as last loaded or saved.
+    saved: HashMap<String, (i64, Vec<Item>)>,
+    /// Archived topics as last loaded or saved.
+    archived: Vec<(String, DateTime<Utc>)>,
+}
+
+impl Sqlite {
+    /// Opens the database at `path`, creating it if need be.
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            path: path.to_owned(),
+            conn,
+            saved: HashMap::new(),
+            archived: vec![],
+        })
+    }
+
+    fn setting<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        self.conn
+            .query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| {
+                row.get::<_, String>(0)
+            })
+            .optional()?
+            .map(|value| serde_json::from_str(&value).map_err(MemoryError::JsonError))
+            .transpose()
+    }
+
+    /// The items of a topic, nested as they were saved.
+    fn items(&self, topic: i64) -> Result<Vec<Item>> {
+        let mut history: HashMap<i64, Vec<DateTime<Utc>>> = HashMap::new();
+        let mut statement = self.conn.prepare(
+            "SELECT history.item, history.completed FROM history
+             JOIN items ON items.id = history.item
+             WHERE items.topic = ?1 ORDER BY history.rowid",
+        )?;
+        let rows = statement.query_map([topic], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        for row in rows {
+            let (id, at) = row?;
+            history.entry(id).or_default().push(at);
+        }
+
+        let mut statement = self.conn.prepare(
+            "SELECT id, parent, done, text, created, modified, completed, due, priority,
+                    recurrence, notes, collapsed, scheduled
+             FROM items WHERE topic = ?1 ORDER BY position",
+        )?;
+        let rows = statement.query_map([topic], |row| {
+            let done: bool = row.get(2)?;
+            let text: String = row.get(3)?;
+            let priority: Option<String> = row.get(8)?;
+            let recurrence: Option<String> = row.get(9)?;
+            let mut item = Item::from(format!("{}{text}", done as u8).as_str());
+            item.created = row.get(4)?;
+            item.modified = row.get(5)?;
+            item.completed = row.get(6)?;
+            item.due = row.get::<_, Option<NaiveDate>>(7)?;
+            item.priority = priority
+                .and_then(|priority| priority.chars().next())
+                .and_then(|c| Priority::try_from(c).ok());
+            item.recurrence = recurrence.and_then(|recurrence| recurrence.parse().ok());
+            item.notes = row.get(10)?;
+            item.collapsed = row.get(11)?;
+            item.scheduled = row.get(12)?;
+            Ok((row.get::<_, i64>(0)?, row.get::<_, Option<i64>>(1)?, item))
+        })?;
+
+        let mut children: HashMap<Option<i64>, Vec<(i64, Item)>> = HashMap::new();
+        for row in rows {
+            let (id, parent, mut item) = row?;
+            item.completions = history.remove(&id).unwrap_or_default();
+            children.entry(parent).or_default().push((id, item));
+        }
+        fn nest(
+            parent: Option<i64>,
+            children: &mut HashMap<Option<i64>, Vec<(i64, Item)>>,
+        ) -> Vec<Item> {
+            children
+                .remove(&parent)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(id, mut item)| {
+                    item.children = nest(Some(id), children);
+                    item
+                })
+                .collect()
+        }
+        Ok(nest(None, &mut children))
+    }
+}
+
+fn insert_items(tx: &Transaction, topic: i64, parent: Option<i64>, items: &[Item]) -> Result<()> {
+    for (position, item) in items.iter().enumerate() {
+        tx.execute(
+            "INSERT INTO items (topic, parent, position, done, text, created, modified,
+                                completed, due, priority, recurrence, notes, collapsed,
+                                scheduled)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            params![
+                topic,
+                parent,
+                position,
+                item.is_done(),
+                item.text(),
+                item.created,
+                item.modified,
+                item.completed,
+                item.due,
+                item.priority.map(|priority| priority.to_string()),
+                item.recurrence.map(|recurrence| recurrence.to_string()),
+                item.notes,
+                item.collapsed,
+                item.scheduled,
+            ],
+        )?;
+        let id = tx.last_insert_rowid();
+        for tag in item.tags() {
+            tx.execute(
+                "INSERT INTO tags (item, tag) VALUES (?1, ?2)",
+                params![id, tag],
+            )?;
+        }
+        for at in &item.completions {
+            tx.execute(
+                "INSERT INTO history (item, completed) VALUES (?1, ?2)",
+                params![id, at],
+            )?;
+        }
+        insert_items(tx, topic, Some(id), &item.children)?;
+    }
+    Ok(())
+}
+
+impl Backend<String, Item> for Sqlite {
+    fn load(&mut self) -> Result<Memory<String, Item>> {
+        let mut memory = Memory::new();
+        memory.topic_sort = self.setting("topic_sort")?.unwrap_or_default();
+        memory.remember_views = self.setting("remember_views")?.unwrap_or_default();
+        memory.auto_archive = self.setting("auto_archive")?.flatten();
+
+        let mut statement = self.conn.prepare(
+            "SELECT id, name, created, done_since, hide_done, sort, archived
+             FROM topics ORDER BY archived IS NOT NULL, position",
+        )?;
+        type Topic = (
+            i64,
+            String,
+            Option<DateTime<Utc>>,
+            Option<DateTime<Utc>>,
+            bool,
+            String,
+            Option<DateTime<Utc>>,
+        );
+        let topics = statement
+            .query_map([], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                    row.get(6)?,
+                ))
+            })?
+            .collect::<std::result::Result<Vec<Topic>, _>>()?;
+        drop(statement);
+
+        self.saved.clear();
+        self.archived.clear();
+        for (id, topic, created, done_since, hide_done, sort, archived) in topics {
+            let items = self.items(id)?;
+            match archived {
+                Some(archived) => {
+                    self.archived.push((topic.clone(), archived));
+                    memory.archive.push(Archived {
+                        topic,
+                        items,
+                        created,
+                        archived,
+                    });
+                }
+                None => {
+                    let view = View {
+                        hide_done,
+                        sort: named(&sort)?,
+                    };
+                    if view != View::default() {
+                        memory.views.insert(topic.clone(), view);
+                    }
+                    if let Some(created) = created {
+                        memory.created.insert(topic.clone(), created);
+                    }
+                    if let Some(done_since) = done_since {
+                        memory.done_since.insert(topic.clone(), done_since);
+                    }
+                    self.saved.insert(topic.clone(), (id, items.clone()));
+                    memory.data.insert(topic.clone(), items);
+                    memory.idxs.push(topic);
+                }
+            }
+        }
+        Ok(memory)
+    }
+
+    fn save(&mut self, memory: &Memory<String, Item>) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        for (key, value) in [
+            ("topic_sort", serde_json::to_string(&memory.topic_sort)?),
+            (
+                "remember_views",
+                serde_json::to_string(&memory.remember_views)?,
+            ),
+            ("auto_archive", serde_json::to_string(&memory.auto_archive)?),
+        ] {
+            tx.execute(
+                "INSERT INTO settings (key, value) VALUES (?1, ?2)
+                 ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+                params![key, value],
+            )?;
+        }
+
+        let mut saved = HashMap::new();
+        for (topic, (id, _)) in &self.saved {
+            if !memory.data.contains_key(topic) {
+                tx.execute("DELETE FROM topics WHERE id = ?1", [id])?;
+            }
+        }
+        for (position, topic) in memory.idxs.iter().enumerate() {
+            let items = memory.data.get(topic).cloned().unwrap_or_default();
+            let view = memory.views.get(topic).cloned().unwrap_or_default();
+            let id = match self.saved.get(topic) {
+                Some((id, before)) => {
+                    if *before != items {
+                        tx.execute("DELETE FROM items WHERE topic = ?1", [id])?;
+                        insert_items(&tx, *id, None, &items)?;
+                    }
+                    *id
+                }
+                // possibly added by another go22dos since, whose items give
+                // way to ours
+                None => {
+                    let id = tx.query_row(
+                        "INSERT INTO topics (name, position) VALUES (?1, ?2)
+                         ON CONFLICT (name) WHERE archived IS NULL
+                         DO UPDATE SET position = excluded.position
+                         RETURNING id",
+                        params![topic, position],
+                        |row| row.get(0),
+                    )?;
+                    tx.execute("DELETE FROM items WHERE topic = ?1", [id])?;
+                    insert_items(&tx, id, None, &items)?;
+                    id
+                }
+            };
+            tx.execute(
+                "UPDATE topics SET name = ?1, position = ?2, created = ?3, done_since = ?4,
+                 hide_done = ?5, sort = ?6 WHERE id = ?7",
+                params![
+                    topic,
+                    position,
+                    memory.created.get(topic),
+                    memory.done_since.get(topic),
+                    view.hide_done,
+                    name(view.sort)?,
+                    id
+                ],
+            )?;
+            saved.insert(topic.clone(), (id, items));
+        }
+
+        let archived = memory
+            .archive
+            .iter()
+            .map(|archived| (archived.topic.clone(), archived.archived))
+            .collect::<Vec<_>>();
+        if archived != self.archived {
+            tx.execute("DELETE FROM topics WHERE archived IS NOT NULL", [])?;
+            for (position, archived) in memory.archive.iter().enumerate() {
+                tx.execute(
+                    "INSERT INTO topics (name, position, created, archived)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![
+                        archived.topic,
+                        position,
+                        archived.created,
+                        archived.archived
+                    ],
+                )?;
+                insert_items(&tx, tx.last_insert_rowid(), None, &archived.items)?;
+            }
+        }
+
+        tx.commit()?;
+        self.saved = saved;
+        self.archived = archived;
+        Ok(())
+    }
+
+    fn path(&self) -> Option<&str> {
+        Some(&self.path)
+    }
+
+    fn saves_each_change(&self) -> bool {
+        true
+    }
+
+    fn watch(&self) -> Option<Watch> {
+        Watch::in_place(&self.path).ok()
+    }
+
+    fn lock(&mut self) -> Result<bool> {
+        // several go22dos are kept apart by transactions and the busy timeout
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::memory::Sort;
+
+    #[test]
+    fn database() {
+        let path = std::env::temp_dir().join(format!("go22dos-{}.db", std::process::id()));
+        let path = path.to_string_lossy().into_owned();
+        let _ = std::fs::remove_file(&path);
+        let now = Utc::now();
+
+        let mut a = Item::new("call #phone due:2026-10-20 rec:weekly", now);
+        a.priority = Priority::try_from('B').ok();
+        a.notes = "first\nsecond".to_owned();
+        a.scheduled = NaiveDate::from_ymd_opt(2026, 10, 19);
+        a.toggle(now);
+        let mut b = Item::new("tidy", now);
+        b.children = vec![Item::new("desk", now), Item::new("shelf #home", now)];
+        b.children[0].children = vec![Item::new("drawer", now)];
+        b.toggle(now);
+
+        let mut memory = Memory::new();
+        memory.idxs = vec!["work".to_owned(), "home".to_owned()];
+        memory.data.insert("work".to_owned(), vec![a, b]);
+        memory.data.insert("home".to_owned(), vec![]);
+        memory.created.insert("work".to_owned(), now);
+        memory.views.insert(
+            "home".to_owned(),
+            View {
+                hide_done: true,
+                sort: Sort::Due,
+            },
+        );
+        memory.auto_archive = Some(7);
+        memory.archive.push(Archived {
+            topic: "old".to_owned(),
+            items: vec![Item::new("gone", now)],
+            created: None,
+            archived: now,
+        });
+
+        let mut sqlite = Sqlite::open(&path).unwrap();
+        sqlite.save(&memory).unwrap();
+        let loaded = Sqlite::open(&path).unwrap().load().unwrap();
+        assert_eq!(loaded.idxs, memory.idxs);
+        assert_eq!(loaded.data, memory.data);
+        assert_eq!(loaded.created, memory.created);
+        assert_eq!(loaded.views, memory.views);
+        assert_eq!(loaded.auto_archive, Some(7));
+        assert_eq!(loaded.archive[0].items, memory.archive[0].items);
+
+        // only what changed is written, the rest stays as it was
+        memory.data.remove("work");
+        memory.idxs.retain(|topic| topic != "work");
+        memory
+            .data
+            .get_mut("home")
+            .unwrap()
+            .push(Item::new("plants", now));
+        sqlite.save(&memory).unwrap();
+        let loaded = sqlite.load().unwrap();
+        assert_eq!(loaded.idxs, vec!["home"]);
+        assert_eq!(loaded.data, memory.data);
+        let count = |table: &str| -> i64 {
+            let sql = format!("SELECT COUNT(*) FROM {table}");
+            sqlite.conn.query_row(&sql, [], |row| row.get(0)).unwrap()
+        };
+        assert_eq!((count("items"), count("tags"), count("history")), (2, 0, 0));
+
+        // the same topic added by another go22dos in the meantime
+        let mut other = Sqlite::open(&path).unwrap();
+        let mut theirs = other.load().unwrap();
+        theirs.idxs.push("new".to_owned());
+        theirs
+            .data
+            .insert("new".to_owned(), vec![Item::new("theirs", now)]);
+        other.save(&theirs).unwrap();
+        memory.idxs.push("new".to_owned());
+        memory
+            .data
+            .insert("new".to_owned(), vec![Item::new("ours", now)]);
+        sqlite.save(&memory).unwrap();
+        let loaded = sqlite.load().unwrap();
+        assert_eq!(loaded.idxs, vec!["home", "new"]);
+        assert_eq!(loaded.data["new"][0].text(), "ours");
+        let _ = std::fs::remove_file(&path);
+    }
+}
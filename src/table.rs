@@ -0,0 +1,290 @@
+//! Items as rows of comma separated values, for spreadsheets:
+//!
+//! ```text
+//! topic,text,status,created,completed,due,priority,tags,path
+//! work,report #q3,open,2026-10-01T09:30:00+00:00,,2026-10-20,A,q3,1
+//! work,figures,done,,,,,,1.1
+//! ```
+//!
+//! Subtasks are rows of their own, right after their parent. The path numbers
+//! an item among its siblings, below the item whose path it extends; rows
+//! without one are top level items. Columns may come in any order, unknown
+//! ones are ignored.
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::item::{self, Item, Priority};
+
+pub const COLUMNS: [&str; 9] = [
+    "topic",
+    "text",
+    "status",
+    "created",
+    "completed",
+    "due",
+    "priority",
+    "tags",
+    "path",
+];
+
+fn row(topic: &str, path: &[usize], item: &Item) -> [String; 9] {
+    let status = match item.is_done() {
+        true => "done",
+        _ => "open",
+    };
+    let time = |at: Option<DateTime<Utc>>| at.map(|at| at.to_rfc3339()).unwrap_or_default();
+    [
+        topic.to_owned(),
+        item.text().to_owned(),
+        status.to_owned(),
+        time(item.created),
+        time(item.completed),
+        item.due.map(|due| due.to_string()).unwrap_or_default(),
+        item.priority
+            .map(|priority| priority.to_string())
+            .unwrap_or_default(),
+        item.tags().join(" "),
+        path.iter()
+            .map(|i| (i + 1).to_string())
+            .collect::<Vec<_>>()
+            .join("."),
+    ]
+}
+
+pub fn export(topics: &[(String, Vec<Item>)]) -> String {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    let rows = topics.iter().flat_map(|(topic, items)| {
+        item::paths(items)
+            .into_iter()
+            .zip(item::walk(items))
+            .map(move |(path, item)| row(topic, &path, item))
+    });
+    // writing to memory does not fail
+    let _ = writer.write_record(COLUMNS);
+    rows.for_each(|row| {
+        let _ = writer.write_record(row);
+    });
+    String::from_utf8(writer.into_inner().unwrap_or_default()).unwrap_or_default()
+}
+
+fn time(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|at| at.with_timezone(&Utc))
+        .ok()
+        .or_else(|| date(s)?.and_hms_opt(0, 0, 0).map(|at| at.and_utc()))
+}
+
+fn date(s: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()
+}
+
+/// The topic, path and item of a row, given the index of each column in it.
+fn item(
+    record: &csv::StringRecord,
+    columns: &[Option<usize>; 9],
+    at: DateTime<Utc>,
+) -> Result<(Option<String>, String, Item), String> {
+    let [topic, text, status, created, completed, due, priority, tags, path] =
+        columns.map(|column| {
+            column
+                .and_then(|i| record.get(i))
+                .map(str::trim)
+                .unwrap_or_default()
+        });
+
+    if text.is_empty() {
+        return Err("no text".to_owned());
+    }
+    let done = match status.to_lowercase().as_str() {
+        "" | "open" | "todo" | "0" => false,
+        "done" | "x" | "1" => true,
+        status => return Err(format!("unknown status {status}")),
+    };
+    let parse = |name: &str, value: &str, parse: fn(&str) -> Option<DateTime<Utc>>| match value {
+        "" => Ok(None),
+        _ => parse(value)
+            .map(Some)
+            .ok_or(format!("invalid {name} {value}")),
+    };
+
+    let mut item = Item::new("", at);
+    // the text is taken as is, not for any `due:` it might contain
+    item.entry = format!("{}{text}", done as u8);
+    item.created = parse("created", created, time)?.or(item.created);
+    item.completed = parse("completed", completed, time)?.or(done.then_some(at));
+    item.due = match due {
+        "" => None,
+        _ => Some(date(due).ok_or(format!("invalid due {due}"))?),
+    };
+    item.priority = match priority {
+        "" => None,
+        _ => Some(
+            priority
+                .chars()
+                .next()
+                .filter(|_| priority.chars().count() == 1)
+                .and_then(|c| Priority::try_from(c.to_ascii_uppercase()).ok())
+                .ok_or(format!("invalid priority {priority}"))?,
+        ),
+    };
+    let tags = tags
+        .split([' ', ','])
+        .map(|tag| tag.trim_start_matches('#'))
+        .filter(|tag| !tag.is_empty() && !item.has_tag(tag))
+        .map(|tag| format!(" #{tag}"))
+        .collect::<String>();
+    item.entry.push_str(&tags);
+
+    Ok((
+        Some(topic.to_owned()).filter(|topic| !topic.is_empty()),
+        path.to_owned(),
+        item,
+    ))
+}
+
+/// Puts `item` below the item with the parent path of `path`, or at the top
+/// level when there is none, and notes where it went.
+fn place(items: &mut Vec<Item>, placed: &mut HashMap<String, Vec<usize>>, path: &str, item: Item) {
+    let parent = path
+        .rsplit_once('.')
+        .and_then(|(parent, _)| placed.get(parent))
+        .cloned()
+        .unwrap_or_default();
+    let siblings = match item::get_mut(items, &parent) {
+        Some(parent) => &mut parent.children,
+        None => items,
+    };
+    siblings.push(item);
+    if !path.is_empty() {
+        placed.insert(
+            path.to_owned(),
+            [&parent[..], &[siblings.len() - 1]].concat(),
+        );
+    }
+}
+
+/// Rows without a topic go to `topic`. Rows which cannot be mapped are left
+/// out and reported with their line.
+pub fn import(s: &str, topic: &str, at: DateTime<Utc>) -> (Vec<(String, Vec<Item>)>, Vec<String>) {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(s.as_bytes());
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(e) => return (vec![], vec![format!("no header: {e}")]),
+    };
+    let columns = COLUMNS.map(|name| {
+        headers
+            .iter()
+            .position(|header| header.trim().eq_ignore_ascii_case(name))
+    });
+    if columns[1].is_none() {
+        return (vec![], vec!["no text column".to_owned()]);
+    }
+
+    let mut topics: Vec<(String, Vec<Item>)> = vec![];
+    // where the paths of each topic ended up
+    let mut placed: HashMap<String, HashMap<String, Vec<usize>>> = HashMap::new();
+    let mut skipped = vec![];
+    reader.records().for_each(|record| {
+        let line = |e: &csv::Error| e.position().map_or(0, |p| p.line());
+        let mapped = record
+            .map_err(|e| (line(&e), e.to_string()))
+            .and_then(|record| {
+                let line = record.position().map_or(0, |p| p.line());
+                item(&record, &columns, at).map_err(|e| (line, e))
+            });
+        match mapped {
+            Ok((project, path, item)) => {
+                let project = project.unwrap_or(topic.to_owned());
+                let i = match topics.iter().position(|(topic, _)| *topic == project) {
+                    Some(i) => i,
+                    None => {
+                        topics.push((project.clone(), vec![]));
+                        topics.len() - 1
+                    }
+                };
+                let placed = placed.entry(project).or_default();
+                place(&mut topics[i].1, placed, &path, item);
+            }
+            Err((line, e)) => skipped.push(format!("line {line}: {e}")),
+        }
+    });
+    (topics, skipped)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rows() {
+        let at = Utc::now();
+        let mut a = Item::new("report, \"final\" #q3 due:2026-10-20", at);
+        a.priority = Priority::try_from('A').ok();
+        a.children = vec![Item::from("1figures")];
+        let topics = vec![("work".to_owned(), vec![a])];
+
+        let s = export(&topics);
+        let mut lines = s.lines();
+        assert_eq!(lines.next(), Some(COLUMNS.join(",").as_str()));
+        assert!(lines.next().is_some_and(|line| line
+            .starts_with("work,\"report, \"\"final\"\" #q3\",open,")
+            && line.ends_with(",,2026-10-20,A,q3,1")));
+        assert_eq!(lines.next(), Some("work,figures,done,,,,,,1.1"));
+
+        let (imported, skipped) = import(&s, "file", at);
+        assert!(skipped.is_empty());
+        assert_eq!(imported[0].1[0].text(), topics[0].1[0].text());
+        assert_eq!(imported[0].1[0].created, topics[0].1[0].created);
+        assert_eq!(imported[0].1[0].due, topics[0].1[0].due);
+        assert_eq!(imported[0].1.len(), 1);
+        assert!(imported[0].1[0].children[0].is_done());
+    }
+
+    #[test]
+    fn subtasks() {
+        let s = "text,path,topic\n\
+                 a,1,\n\
+                 b,2,\n\
+                 b1,2.1,\n\
+                 b11,2.1.1,\n\
+                 a1,1.1,\n\
+                 orphan,3.1,\n\
+                 loose,,\n\
+                 other,1.1,elsewhere\n";
+        let (topics, skipped) = import(s, "file", Utc::now());
+        assert!(skipped.is_empty());
+        fn texts(items: &[Item]) -> Vec<&str> {
+            items.iter().map(Item::text).collect()
+        }
+        let items = &topics[0].1;
+        assert_eq!(texts(items), vec!["a", "b", "orphan", "loose"]);
+        assert_eq!(texts(&items[0].children), vec!["a1"]);
+        assert_eq!(texts(&items[1].children), vec!["b1"]);
+        assert_eq!(texts(&items[1].children[0].children), vec!["b11"]);
+        assert_eq!(texts(&topics[1].1), vec!["other"]);
+    }
+
+    #[test]
+    fn columns_in_any_order() {
+        let at = Utc::now();
+        let s = "Tags,Due,Text,Owner\n\
+                 phone,2026-10-20,call mom,me\n\
+                 ,someday,call dad,me\n\
+                 ,,,me\n\
+                 a b,,tidy #a,\n";
+        let (topics, skipped) = import(s, "file", at);
+        assert_eq!(topics[0].0, "file");
+        let texts = topics[0].1.iter().map(Item::text).collect::<Vec<_>>();
+        assert_eq!(texts, vec!["call mom #phone", "tidy #a #b"]);
+        assert_eq!(topics[0].1[0].due, date("2026-10-20"));
+        assert_eq!(
+            skipped,
+            vec!["line 3: invalid due someday", "line 4: no text"]
+        );
+
+        assert_eq!(import("a,b\n1,2\n", "file", at).1, vec!["no text column"]);
+    }
+}
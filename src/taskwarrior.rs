@@ -0,0 +1,389 @@
+//! Taskwarrior's JSON, as `task export` writes it and `task import` reads it.
+//! Projects are topics, H/M/L priorities are `A` to `C`, and annotations are
+//! notes. Subtasks are tasks their parent depends on. Deleted tasks and the
+//! templates of recurring ones are left out, tasks are read in the order of
+//! their urgency.
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::date::Recurrence;
+use crate::item::{Item, Priority};
+
+const DATE_TIME: &str = "%Y%m%dT%H%M%SZ";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Annotation {
+    entry: String,
+    description: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Task {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    uuid: Option<String>,
+    description: String,
+    status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    project: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    entry: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    modified: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    end: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    due: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    priority: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recur: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    annotations: Vec<Annotation>,
+    /// older versions have a comma separated string here
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    depends: serde_json::Value,
+    #[serde(default, skip_serializing)]
+    urgency: f64,
+}
+
+fn stamp(at: DateTime<Utc>) -> String {
+    at.format(DATE_TIME).to_string()
+}
+
+fn time(s: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(s, DATE_TIME)
+        .map(|at| at.and_utc())
+        .ok()
+        .or_else(|| {
+            DateTime::parse_from_rfc3339(s)
+                .ok()
+                .map(|at| at.with_timezone(&Utc))
+        })
+}
+
+/// FNV-1a, which unlike the hasher of the standard library stays the same
+/// across Rust releases.
+fn fnv(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// A made up uuid for an item, which stays the same wherever the item is
+/// moved, as long as it is the same item.
+fn uuid(item: &Item) -> String {
+    let created = item.created.map(stamp).unwrap_or_default();
+    let text = item.text();
+    let hash = |seed: u8| fnv(format!("{seed}\0{created}\0{text}").as_bytes());
+    let (a, b) = (hash(0), hash(1));
+    format!(
+        "{:08x}-{:04x}-4{:03x}-{:04x}-{:012x}",
+        a >> 32,
+        (a >> 16) & 0xffff,
+        a & 0xfff,
+        0x8000 | (b >> 48) & 0x3fff,
+        b & 0xffff_ffff_ffff
+    )
+}
+
+fn recur(recurrence: Recurrence) -> String {
+    match recurrence {
+        Recurrence::Daily => "daily".to_owned(),
+        Recurrence::Weekly(_) => "weekly".to_owned(),
+        Recurrence::Monthly => "monthly".to_owned(),
+        Recurrence::Every(days) => format!("{days}d"),
+    }
+}
+
+fn write_tasks(items: &[Item], topic: &str, tasks: &mut Vec<Task>) -> Vec<String> {
+    items
+        .iter()
+        .map(|item| {
+            let uuid = uuid(item);
+            let depends = write_tasks(&item.children, topic, tasks);
+
+            let stamp_of = item
+                .modified
+                .or(item.created)
+                .map(stamp)
+                .unwrap_or_default();
+            tasks.push(Task {
+                uuid: Some(uuid.clone()),
+                description: item.text().to_owned(),
+                status: match item.is_done() {
+                    true => "completed".to_owned(),
+                    _ => "pending".to_owned(),
+                },
+                project: Some(topic.to_owned()),
+                entry: item.created.map(stamp),
+                modified: item.modified.map(stamp),
+                end: item.completed.map(stamp),
+                // due dates are meant to be local
+                due: item
+                    .due
+                    .and_then(|due| {
+                        due.and_hms_opt(0, 0, 0)?
+                            .and_local_timezone(Local)
+                            .earliest()
+                    })
+                    .map(|due| stamp(due.with_timezone(&Utc))),
+                priority: item.priority.map(|priority| {
+                    match priority.letter() {
+                        'A' => "H",
+                        'B' => "M",
+                        _ => "L",
+                    }
+                    .to_owned()
+                }),
+                recur: item.recurrence.map(recur),
+                tags: item.tags().into_iter().map(str::to_owned).collect(),
+                annotations: match item.notes.is_empty() {
+                    true => vec![],
+                    _ => vec![Annotation {
+                        entry: stamp_of,
+                        description: item.notes.clone(),
+                    }],
+                },
+                depends: match depends.is_empty() {
+                    true => serde_json::Value::Null,
+                    _ => depends.into(),
+                },
+                urgency: 0.0,
+            });
+            uuid
+        })
+        .collect()
+}
+
+pub fn export(topics: &[(String, Vec<Item>)]) -> String {
+    let mut tasks = vec![];
+    topics.iter().for_each(|(topic, items)| {
+        write_tasks(items, topic, &mut tasks);
+    });
+    serde_json::to_string_pretty(&tasks).unwrap_or_default() + "\n"
+}
+
+fn item(task: &Task, at: DateTime<Utc>) -> Item {
+    let mut item = Item::new("", at);
+    // the text is taken as is, not for any `due:` it might contain
+    item.entry = format!("{}{}", (task.status == "completed") as u8, task.description);
+    item.created = task.entry.as_deref().and_then(time);
+    item.modified = task.modified.as_deref().and_then(time);
+    item.completed = match item.is_done() {
+        true => task.end.as_deref().and_then(time).or(Some(at)),
+        _ => None,
+    };
+    item.due = task
+        .due
+        .as_deref()
+        .and_then(time)
+        .map(|due| due.with_timezone(&Local).date_naive());
+    item.priority = match task.priority.as_deref() {
+        Some("H") => Priority::try_from('A').ok(),
+        Some("M") => Priority::try_from('B').ok(),
+        Some("L") => Priority::try_from('C').ok(),
+        _ => None,
+    };
+    item.recurrence = task.recur.as_deref().and_then(|recur| {
+        let today = item.due.unwrap_or(at.with_timezone(&Local).date_naive());
+        Recurrence::parse(recur, today)
+    });
+    item.notes = task
+        .annotations
+        .iter()
+        .map(|annotation| annotation.description.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let tags = task
+        .tags
+        .iter()
+        .filter(|tag| !item.has_tag(tag))
+        .map(|tag| format!(" #{tag}"))
+        .collect::<String>();
+    item.entry.push_str(&tags);
+    item
+}
+
+fn depends(task: &Task) -> Vec<String> {
+    match &task.depends {
+        serde_json::Value::Array(uuids) => uuids
+            .iter()
+            .filter_map(|uuid| uuid.as_str().map(str::to_owned))
+            .collect(),
+        serde_json::Value::String(uuids) => uuids.split(',').map(str::to_owned).collect(),
+        _ => vec![],
+    }
+}
+
+/// The item at `i` with its subtasks, taken out of `items`.
+fn adopt(
+    i: usize,
+    items: &mut Vec<Option<Item>>,
+    children: &HashMap<usize, Vec<usize>>,
+) -> Option<Item> {
+    let mut item = items[i].take()?;
+    item.children = children
+        .get(&i)
+        .into_iter()
+        .flatten()
+        .filter_map(|child| adopt(*child, items, children))
+        .collect();
+    Some(item)
+}
+
+/// Tasks without a project go to `topic`, what cannot be read is reported.
+pub fn import(s: &str, topic: &str, at: DateTime<Utc>) -> (Vec<(String, Vec<Item>)>, Vec<String>) {
+    // `task export` has written a task per line in older versions
+    let mut tasks = match serde_json::from_str::<Vec<Task>>(s) {
+        Ok(tasks) => tasks,
+        Err(e) if s.trim_start().starts_with('[') => return (vec![], vec![e.to_string()]),
+        Err(_) => {
+            let mut skipped = vec![];
+            let tasks = s
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| !line.trim().is_empty())
+                .filter_map(|(i, line)| {
+                    serde_json::from_str(line.trim().trim_end_matches(','))
+                        .map_err(|e| skipped.push(format!("line {}: {e}", i + 1)))
+                        .ok()
+                })
+                .collect();
+            if !skipped.is_empty() {
+                return (vec![], skipped);
+            }
+            tasks
+        }
+    };
+    tasks.retain(|task| task.status != "deleted" && task.status != "recurring");
+    tasks.sort_by(|a, b| b.urgency.total_cmp(&a.urgency));
+
+    let index = tasks
+        .iter()
+        .enumerate()
+        .filter_map(|(i, task)| Some((task.uuid.clone()?, i)))
+        .collect::<HashMap<_, _>>();
+    // a task is the subtask of the first task of its project depending on it,
+    // unless that one already is a subtask of it
+    let mut parents: HashMap<usize, usize> = HashMap::new();
+    tasks.iter().enumerate().for_each(|(i, task)| {
+        depends(task)
+            .iter()
+            .filter_map(|uuid| index.get(uuid.trim()).copied())
+            .filter(|j| tasks[*j].project == task.project)
+            .for_each(|j| {
+                let mut ancestor = Some(i);
+                while let Some(a) = ancestor.filter(|a| *a != j) {
+                    ancestor = parents.get(&a).copied();
+                }
+                if ancestor.is_none() && !parents.contains_key(&j) {
+                    parents.insert(j, i);
+                }
+            });
+    });
+    let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut adopted = parents.keys().copied().collect::<Vec<_>>();
+    // in the order of their urgency
+    adopted.sort();
+    adopted
+        .iter()
+        .for_each(|j| children.entry(parents[j]).or_default().push(*j));
+    let adopted = adopted.into_iter().collect::<HashSet<_>>();
+
+    let mut items = tasks.iter().map(|task| Some(item(task, at))).collect();
+    let mut topics: Vec<(String, Vec<Item>)> = vec![];
+    (0..tasks.len())
+        .filter(|i| !adopted.contains(i))
+        .for_each(|i| {
+            let project = tasks[i].project.clone().unwrap_or(topic.to_owned());
+            let Some(item) = adopt(i, &mut items, &children) else {
+                return;
+            };
+            match topics.iter_mut().find(|(topic, _)| *topic == project) {
+                Some((_, items)) => items.push(item),
+                None => topics.push((project, vec![item])),
+            }
+        });
+    (topics, vec![])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tasks() {
+        let s = r#"[
+            {"id":0,"description":"low","status":"pending","project":"home","urgency":1.5,
+             "uuid":"a","tags":["garden"],"entry":"20261001T093000Z"},
+            {"id":1,"description":"high","status":"pending","project":"home","urgency":9.1,
+             "uuid":"b","priority":"H","depends":"c,a","annotations":[{"entry":"20261002T000000Z","description":"why"}]},
+            {"id":0,"description":"done","status":"completed","end":"20261018T120000Z","uuid":"c","project":"home"},
+            {"id":0,"description":"gone","status":"deleted","uuid":"d"},
+            {"id":2,"description":"loose","status":"waiting","uuid":"e","recur":"weekly","due":"20261020T000000Z"}
+        ]"#;
+        let at = Utc::now();
+        let (topics, skipped) = import(s, "tasks", at);
+        assert!(skipped.is_empty());
+        assert_eq!(topics.len(), 2);
+        let high = &topics[0].1[0];
+        assert_eq!(topics[0].1.len(), 1);
+        assert_eq!(high.text(), "high");
+        assert_eq!(high.notes, "why");
+        assert_eq!(high.priority, Priority::try_from('A').ok());
+        let texts = high.children.iter().map(Item::text).collect::<Vec<_>>();
+        assert_eq!(texts, vec!["low #garden", "done"]);
+        assert!(high.children[1].is_done());
+        assert_eq!(high.children[1].completed, time("20261018T120000Z"));
+        assert_eq!(topics[1].0, "tasks");
+        assert!(topics[1].1[0].recurrence.is_some());
+
+        let exported = export(&topics);
+        let (again, _) = import(&exported, "other", at);
+        assert_eq!(again, topics);
+    }
+
+    #[test]
+    fn tasks_per_line() {
+        let s = "{\"description\":\"one\",\"status\":\"pending\"},\n{\"description\":\"two\",\"status\":\"pending\"}\n";
+        let (topics, skipped) = import(s, "tasks", Utc::now());
+        assert!(skipped.is_empty());
+        assert_eq!(topics[0].1.len(), 2);
+        assert_eq!(import("[{]", "tasks", Utc::now()).1.len(), 1);
+
+        // depending on each other
+        let s = r#"[{"description":"a","status":"pending","uuid":"a","depends":["b"]},
+                    {"description":"b","status":"pending","uuid":"b","depends":["a"]}]"#;
+        let (topics, _) = import(s, "tasks", Utc::now());
+        assert_eq!(topics[0].1[0].text(), "a");
+        assert_eq!(topics[0].1[0].children[0].text(), "b");
+    }
+
+    #[test]
+    fn stable_uuids() {
+        let item = Item::new("a", time("20261018T120000Z").unwrap());
+        // the same in every build, so that tasks exported again are updated
+        assert_eq!(uuid(&item), "b659b78c-d997-44eb-b6ef-7ef853b783ca");
+
+        // and wherever the item went since, here from a subtask to the top
+        // of another topic
+        let mut parent = Item::new("b", time("20261018T120000Z").unwrap());
+        parent.children = vec![item.clone()];
+        let uuid_of = |topics: &[(String, Vec<Item>)]| {
+            let tasks: Vec<Task> = serde_json::from_str(&export(topics)).unwrap();
+            tasks
+                .into_iter()
+                .find(|task| task.description == "a")
+                .unwrap()
+                .uuid
+        };
+        assert_eq!(
+            uuid_of(&[("work".to_owned(), vec![Item::from("0c"), parent])]),
+            uuid_of(&[("home".to_owned(), vec![item])])
+        );
+    }
+}
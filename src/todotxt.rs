@@ -0,0 +1,342 @@
+//! The todo.txt format, one todo per line:
+//!
+//! ```text
+//! (A) 2026-10-01 +family call mom @phone due:2026-10-20
+//! x 2026-10-18 2026-10-01 +home pay rent pri:B notes:paid%20by%20card
+//! ```
+//!
+//! The first `+project` of a line is its topic, `@context`s are tags.
+//! Subtasks carry `p:` with the `id:` of their parent. The project is written
+//! right before the text, so that the text is never taken for a date or a
+//! priority. Spaces in projects become `_`, and other characters which cannot
+//! be in a word are percent-encoded, as in `notes:`.
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+
+use crate::item::{self, Item, Priority};
+
+fn date(word: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(word, "%Y-%m-%d").ok()
+}
+
+fn midnight(date: NaiveDate) -> DateTime<Utc> {
+    date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc()
+}
+
+/// `s` with `%` and the characters for which `escaped` holds as `%XX`.
+fn escape(s: &str, escaped: impl Fn(char) -> bool) -> String {
+    s.chars()
+        .map(|c| match c == '%' || escaped(c) {
+            true => c
+                .encode_utf8(&mut [0; 4])
+                .bytes()
+                .map(|byte| format!("%{byte:02X}"))
+                .collect(),
+            _ => c.to_string(),
+        })
+        .collect()
+}
+
+fn unescape(s: &str) -> String {
+    let mut bytes = vec![];
+    let mut rest = s.as_bytes();
+    while let Some((byte, tail)) = rest.split_first() {
+        let hex = tail
+            .get(..2)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (byte, hex) {
+            (b'%', Some(hex)) => {
+                bytes.push(hex);
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(*byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn project(topic: &str) -> String {
+    let topic = escape(topic, |c| c == '_' || (c.is_whitespace() && c != ' '));
+    format!("+{}", topic.replace(' ', "_"))
+}
+
+fn topic(project: &str) -> String {
+    unescape(&project.replace('_', " "))
+}
+
+fn line(item: &Item, topic: &str, id: Option<usize>, parent: Option<usize>) -> String {
+    let mut words = vec![];
+    match (item.is_done(), item.completed) {
+        (true, Some(completed)) => {
+            words.push("x".to_owned());
+            words.push(completed.date_naive().to_string());
+            words.extend(item.created.map(|created| created.date_naive().to_string()));
+        }
+        // a lone date after `x` would be taken for the completion date
+        (true, None) => words.push("x".to_owned()),
+        _ => {
+            words.extend(item.priority.map(|priority| format!("({priority})")));
+            words.extend(item.created.map(|created| created.date_naive().to_string()));
+        }
+    }
+    words.push(project(topic));
+    words.extend(
+        item.text()
+            .split(' ')
+            .filter(|word| !word.is_empty())
+            .map(|word| match item::is_tag(word) {
+                true => format!("@{}", &word[1..]),
+                _ => word.to_owned(),
+            }),
+    );
+    words.extend(item.due.map(|due| format!("due:{due}")));
+    words.extend(
+        item.recurrence
+            .map(|recurrence| format!("rec:{recurrence}")),
+    );
+    if item.is_done() {
+        words.extend(item.priority.map(|priority| format!("pri:{priority}")));
+    }
+    // no more than the creation date, unless changed since
+    words.extend(
+        item.modified
+            .filter(|_| item.modified != item.created)
+            .map(|modified| {
+                format!(
+                    "modified:{}",
+                    modified.to_rfc3339_opts(SecondsFormat::Secs, true)
+                )
+            }),
+    );
+    if !item.notes.is_empty() {
+        words.push(format!(
+            "notes:{}",
+            escape(&item.notes, char::is_whitespace)
+        ));
+    }
+    words.extend(id.map(|id| format!("id:{id}")));
+    words.extend(parent.map(|parent| format!("p:{parent}")));
+    words.join(" ")
+}
+
+fn write_lines(
+    items: &[Item],
+    topic: &str,
+    parent: Option<usize>,
+    ids: &mut usize,
+    lines: &mut Vec<String>,
+) {
+    items.iter().for_each(|item| {
+        let id = match item.children.is_empty() {
+            true => None,
+            _ => {
+                *ids += 1;
+                Some(*ids)
+            }
+        };
+        lines.push(line(item, topic, id, parent));
+        write_lines(&item.children, topic, id, ids, lines);
+    });
+}
+
+pub fn export(topics: &[(String, Vec<Item>)]) -> String {
+    let mut ids = 0;
+    let mut lines = vec![];
+    topics
+        .iter()
+        .for_each(|(topic, items)| write_lines(items, topic, None, &mut ids, &mut lines));
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+/// A todo of a line, with its topic and the ids of itself and its parent.
+type Line = (Option<String>, Item, Option<String>, Option<String>);
+
+fn parse(line: &str, at: DateTime<Utc>) -> Option<Line> {
+    let mut words = line.split_whitespace().peekable();
+    words.peek()?;
+
+    let done = words.next_if_eq(&"x").is_some();
+    let priority = match done {
+        true => None,
+        _ => words
+            .next_if(|word| word.len() == 3 && word.starts_with('(') && word.ends_with(')'))
+            .and_then(|word| Priority::try_from(word.chars().nth(1)?).ok()),
+    };
+    let completed = match done {
+        true => words.next_if(|word| date(word).is_some()).and_then(date),
+        _ => None,
+    };
+    let created = words.next_if(|word| date(word).is_some()).and_then(date);
+
+    let (mut project, mut id, mut parent) = (None, None, None);
+    let (mut modified, mut notes) = (None, String::new());
+    let mut priority = priority;
+    let mut text = vec![];
+    for word in words {
+        match word.split_once(':') {
+            Some(("pri", pri)) if pri.len() == 1 => {
+                priority = pri.chars().next().and_then(|c| Priority::try_from(c).ok())
+            }
+            Some(("id", n)) => id = Some(n.to_owned()),
+            Some(("p", n)) => parent = Some(n.to_owned()),
+            Some(("modified", at)) => match DateTime::parse_from_rfc3339(at) {
+                Ok(at) => modified = Some(at.with_timezone(&Utc)),
+                _ => text.push(word.to_owned()),
+            },
+            Some(("notes", s)) => notes = unescape(s),
+            _ => match (word.strip_prefix('+'), word.strip_prefix('@')) {
+                (Some(name), _) if project.is_none() && !name.is_empty() => {
+                    project = Some(topic(name))
+                }
+                (_, Some(context)) if !context.is_empty() => text.push(format!("#{context}")),
+                _ => text.push(word.to_owned()),
+            },
+        }
+    }
+
+    let mut item = Item::new(text.join(" "), at);
+    item.created = created.map(midnight);
+    item.modified = modified.or(item.created);
+    item.priority = priority;
+    item.notes = notes;
+    // not toggled, which would keep recurring items open
+    if done {
+        item.entry = format!("1{}", item.text());
+        item.completed = Some(completed.map_or(at, midnight));
+    }
+    Some((project, item, id, parent))
+}
+
+/// Lines without a project go to `topic`.
+pub fn import(s: &str, topic: &str, at: DateTime<Utc>) -> Vec<(String, Vec<Item>)> {
+    let mut topics: Vec<(String, Vec<Item>)> = vec![];
+    // where the parents are, by topic and id
+    let mut parents: HashMap<(usize, String), Vec<usize>> = HashMap::new();
+
+    s.lines()
+        .filter_map(|line| parse(line, at))
+        .for_each(|(project, item, id, parent)| {
+            let project = project.unwrap_or(topic.to_owned());
+            let on = match topics.iter().position(|(topic, _)| *topic == project) {
+                Some(on) => on,
+                None => {
+                    topics.push((project, vec![]));
+                    topics.len() - 1
+                }
+            };
+            let items = &mut topics[on].1;
+            let path = match parent.and_then(|parent| parents.get(&(on, parent))) {
+                Some(path) => {
+                    let Some(parent) = item::get_mut(items, path) else {
+                        return;
+                    };
+                    parent.children.push(item);
+                    [path.as_slice(), &[parent.children.len() - 1]].concat()
+                }
+                None => {
+                    items.push(item);
+                    vec![items.len() - 1]
+                }
+            };
+            if let Some(id) = id {
+                parents.insert((on, id), path);
+            }
+        });
+    topics
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lines() {
+        let at = Utc::now();
+        let s = "(A) 2026-10-01 call mom +family @phone due:2026-10-20\n\
+                 x 2026-10-18 2026-10-01 pay rent +home pri:B\n\
+                 \n\
+                 water plants rec:3d\n\
+                 x 2026-10-17 water plants rec:3d\n";
+        let topics = import(s, "todo", at);
+        let names = topics
+            .iter()
+            .map(|(topic, _)| topic.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["family", "home", "todo"]);
+
+        let call = &topics[0].1[0];
+        assert_eq!(call.text(), "call mom #phone");
+        assert_eq!(call.priority, Priority::try_from('A').ok());
+        assert_eq!(call.due, date("2026-10-20"));
+        assert_eq!(call.created, date("2026-10-01").map(midnight));
+
+        let rent = &topics[1].1[0];
+        assert!(rent.is_done());
+        assert_eq!(rent.completed, date("2026-10-18").map(midnight));
+        assert_eq!(rent.priority, Priority::try_from('B').ok());
+        assert!(topics[2].1[1].is_done());
+
+        let mut lines = export(&topics)
+            .lines()
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+        lines.truncate(2);
+        assert_eq!(
+            lines,
+            vec![
+                "(A) 2026-10-01 +family call mom @phone due:2026-10-20",
+                "x 2026-10-18 2026-10-01 +home pay rent pri:B",
+            ]
+        );
+    }
+
+    #[test]
+    fn subtasks_keep_their_parents() {
+        let at = Utc::now();
+        let mut a = Item::new("a", at);
+        let mut b = Item::new("b", at);
+        b.children = vec![Item::new("c", at)];
+        a.children = vec![b, Item::new("d", at)];
+        let topics = vec![("my list".to_owned(), vec![a, Item::new("e", at)])];
+
+        let s = export(&topics);
+        assert!(s.contains("+my_list b id:2 p:1\n"));
+        let imported = import(&s, "todo", at);
+        assert_eq!(imported[0].0, "my list");
+        let texts = |items: &[Item]| {
+            item::walk(items)
+                .iter()
+                .map(|item| item.text().to_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(texts(&imported[0].1), texts(&topics[0].1));
+        assert_eq!(imported[0].1[0].children[0].children[0].text(), "c");
+        assert_eq!(imported[0].1.len(), 2);
+    }
+
+    #[test]
+    fn round_trip() {
+        let at = date("2026-10-01").map(midnight).unwrap();
+        let mut plus = Item::new("read +word first", at);
+        plus.notes = "line  one\n100% done\tor not".to_owned();
+        plus.modified = Some(at + chrono::Duration::hours(3));
+        let mut x = Item::from("0x marks the spot");
+        x.modified = None;
+        let mut dated = Item::new("2026-10-05 review", at);
+        dated.created = None;
+        dated.modified = None;
+        let topics = vec![
+            ("my_list 100%".to_owned(), vec![plus]),
+            ("open\tlist".to_owned(), vec![x, dated]),
+        ];
+
+        let s = export(&topics);
+        assert!(s.starts_with("2026-10-01 +my%5Flist_100%25 read +word first"));
+        assert_eq!(import(&s, "todo", Utc::now()), topics);
+    }
+}
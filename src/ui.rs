@@ -1,9 +1,11 @@
 use std::fmt;
 
+use chrono::{DateTime, Local, Utc};
 use ncurses::*;
 use thiserror::Error;
 
 use crate::config;
+use crate::item::{self, Item, Priority};
 use crate::memory;
 
 type Result<T> = std::result::Result<T, UserInterfaceError>;
@@ -27,65 +29,168 @@ where
 {
     fn display_topic_ids(&self, on: usize) -> Result<()>;
     fn display_items(&self, on_topic: usize, on_item: usize) -> Result<()>;
+    fn display_archive(&self, on: usize) -> Result<()>;
+    fn display_tagged(&self, tag: &str, on: usize) -> Result<()>;
 }
-impl<K, V, T> UserInterface<K, V> for T
+
+fn priority_pair(priority: Priority) -> attr_t {
+    match priority.letter() {
+        'A' => COLOR_PAIR(config::PRIORITY_HIGH_PAIR),
+        'B' => COLOR_PAIR(config::PRIORITY_MEDIUM_PAIR),
+        _ => COLOR_PAIR(config::OTHER_PAIR),
+    }
+}
+
+/// Relative age such as `3d`.
+fn age(since: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let d = now - since;
+    match (d.num_days(), d.num_hours(), d.num_minutes()) {
+        (0, 0, 0) => "now".to_owned(),
+        (0, 0, m) => format!("{m}m"),
+        (0, h, _) => format!("{h}h"),
+        (d, _, _) => format!("{d}d"),
+    }
+}
+
+/// Displays an item on the current line: checkbox, priority, `prefix`, the
+/// text with its tags and the remaining fields.
+fn display_item(item: &Item, prefix: &str, highlight: bool, now: DateTime<Utc>) {
+    let today = now.with_timezone(&Local).date_naive();
+    let (cp, s) = match item.as_ref().chars().next() {
+        Some('0') if item.is_overdue(today) => (COLOR_PAIR(config::OVERDUE_PAIR), "[ ]"),
+        Some('0') => (COLOR_PAIR(config::CHECKBOX_TODO_PAIR), "[ ]"),
+        Some('1') => (COLOR_PAIR(config::CHECKBOX_DONE_PAIR), "[X]"),
+        _ => (COLOR_PAIR(config::OTHER_PAIR), "[?]"),
+    };
+    match highlight {
+        true => {
+            addstr(s);
+        }
+        _ => {
+            attron(cp);
+            addstr(s);
+            attroff(cp);
+        }
+    }
+    addstr(" ");
+    match item.priority {
+        Some(priority) => {
+            attron(priority_pair(priority));
+            addstr(&priority.to_string());
+            attroff(priority_pair(priority));
+        }
+        None => {
+            addstr(" ");
+        }
+    }
+    addstr("\t ");
+    addstr(prefix);
+    match highlight {
+        true => {
+            attron(COLOR_PAIR(config::HIGHLIGHT_PAIR));
+            addstr(item.text());
+            attroff(COLOR_PAIR(config::HIGHLIGHT_PAIR));
+        }
+        _ => item.text().split(' ').enumerate().for_each(|(i, word)| {
+            if i > 0 {
+                addstr(" ");
+            }
+            match item::is_tag(word) {
+                true => {
+                    attron(COLOR_PAIR(config::TAG_PAIR));
+                    addstr(word);
+                    attroff(COLOR_PAIR(config::TAG_PAIR));
+                }
+                _ => {
+                    addstr(word);
+                }
+            }
+        }),
+    }
+    if !item.children.is_empty() {
+        addstr(" ");
+        attron(COLOR_PAIR(config::OTHER_PAIR));
+        addstr(&format!("[{:.2}]", item.progress()));
+        attroff(COLOR_PAIR(config::OTHER_PAIR));
+    }
+    if let Some(due) = item.due {
+        let cp = match item.is_overdue(today) {
+            true => COLOR_PAIR(config::OVERDUE_PAIR),
+            _ => COLOR_PAIR(config::OTHER_PAIR),
+        };
+        addstr(" ");
+        attron(cp);
+        addstr(&format!("due:{due}"));
+        attroff(cp);
+    }
+    if let Some(recurrence) = item.recurrence {
+        addstr(" ");
+        attron(COLOR_PAIR(config::OTHER_PAIR));
+        addstr(&format!("rec:{recurrence}"));
+        attroff(COLOR_PAIR(config::OTHER_PAIR));
+    }
+    // done items show how long ago they were done
+    if let Some(since) = item.completed.or(item.created) {
+        addstr(&format!(" {}", age(since, now)));
+    }
+}
+
+/// Shows the notes of the highlighted item in a pane at the bottom of the
+/// screen, above the status line.
+fn display_notes(notes: &str) {
+    if notes.is_empty() {
+        return;
+    }
+    let (mut x, mut y) = (0, 0);
+    getmaxyx(stdscr(), &mut y, &mut x);
+    let lines = notes.lines().collect::<Vec<_>>();
+    let h = lines.len().min((y / 3) as usize) as i32;
+    let top = y - 2 - h;
+
+    mv(top, 0);
+    attron(COLOR_PAIR(config::OTHER_PAIR));
+    addstr(&"-".repeat(x as usize));
+    attroff(COLOR_PAIR(config::OTHER_PAIR));
+    lines
+        .iter()
+        .take(h as usize)
+        .enumerate()
+        .for_each(|(i, line)| {
+            mv(top + 1 + i as i32, 0);
+            clrtoeol();
+            addstr(&line.chars().take(x as usize).collect::<String>());
+        });
+}
+
+impl<K, T> UserInterface<K, Item> for T
 where
-    T: memory::MemoryManagement<K, V>,
+    T: memory::MemoryManagement<K, Item>,
     K: memory::Serializable + std::fmt::Debug,
-    V: memory::Serializable + std::fmt::Debug + Into<String>,
 {
     fn display_topic_ids(&self, on: usize) -> Result<()> {
         let ctx = self.lock_unwrap();
+        let today = Local::now().date_naive();
         match ctx.idxs.is_empty() {
             true => {
                 addstr("no topics atm");
             }
             _ => {
                 mv(1, 0);
-                ctx.idxs.iter().enumerate().for_each(|(i, k)| {
-                    let j = i as i32;
-                    mv(j, 0);
-                    match j == on as i32 {
+                ctx.topic_view().into_iter().enumerate().for_each(|(j, i)| {
+                    let k = &ctx.idxs[i];
+                    mv(j as i32, 0);
+                    let items = ctx.data.get(k).ok_or(UserInterfaceError::Unknown).unwrap();
+                    let q = memory::ratio(items);
+                    match i == on {
                         true => {
-                            let topic =
-                                ctx.idxs.get(on).ok_or(UserInterfaceError::Unknown).unwrap();
-                            let items = ctx
-                                .data
-                                .get(topic)
-                                .ok_or(UserInterfaceError::Unknown)
-                                .unwrap();
-                            let n = items.len() as f32;
-                            let m = items
-                                .iter()
-                                .filter(|item| item.to_string().starts_with('1'))
-                                .count() as f32;
-
-                            addstr(&format!("[{:.2}]\t ", m / n));
+                            addstr(&format!("[{:.2}]\t ", q));
 
                             attron(COLOR_PAIR(config::HIGHLIGHT_PAIR));
-                            addstr(&k.as_ref());
+                            addstr(k.as_ref());
                             attroff(COLOR_PAIR(config::HIGHLIGHT_PAIR));
                         }
                         _ => {
                             //addstr(&format!("[topic]\t {}", k));
-                            let topic = ctx
-                                .idxs
-                                .get(j as usize)
-                                .ok_or(UserInterfaceError::Unknown)
-                                .unwrap();
-                            let items = ctx
-                                .data
-                                .get(topic)
-                                .ok_or(UserInterfaceError::Unknown)
-                                .unwrap();
-                            let n = items.len() as f32;
-                            let m = items
-                                .iter()
-                                .filter(|item| item.to_string().starts_with('1'))
-                                .count() as f32;
-
-                            let q = m / n;
-
                             match q {
                                 1.0 => {
                                     attron(COLOR_PAIR(config::CHECKBOX_DONE_PAIR));
@@ -105,9 +210,34 @@ where
                             }
 
                             addstr("\t ");
-                            addstr(&k.as_ref());
+                            addstr(k.as_ref());
                         }
                     }
+
+                    let overdue = item::walk(items)
+                        .into_iter()
+                        .filter(|item| item.is_overdue(today))
+                        .count();
+                    if overdue > 0 {
+                        addstr(" ");
+                        attron(COLOR_PAIR(config::OVERDUE_PAIR));
+                        addstr(&format!("[{overdue} overdue]"));
+                        attroff(COLOR_PAIR(config::OVERDUE_PAIR));
+                    }
+
+                    // the open item with the highest priority, first one on ties
+                    let top = item::walk(items)
+                        .into_iter()
+                        .filter(|item| !item.is_done())
+                        .filter_map(|item| Some((item.priority?, item)))
+                        .min_by_key(|(priority, _)| *priority);
+                    if let Some((priority, item)) = top {
+                        addstr("  ");
+                        attron(priority_pair(priority));
+                        addstr(&priority.to_string());
+                        attroff(priority_pair(priority));
+                        addstr(&format!(" {}", item.text()));
+                    }
                 });
             }
         }
@@ -119,39 +249,93 @@ where
 
         let topic = ctx.idxs.get(on_topic).ok_or(UserInterfaceError::Unknown)?;
         let items = ctx.data.get(topic).ok_or(UserInterfaceError::Unknown)?;
-
-        let _y = items.len() as i32;
-
-        let w = stdscr();
-        let _x = getmaxx(w);
+        let rows = ctx.view(on_topic);
+        let folded = ctx.folded(on_topic);
+        let now = Utc::now();
 
         match items.is_empty() {
             true => {
                 addstr("no items atm");
             }
-            _ => items.iter().enumerate().for_each(|(i, item)| {
+            _ => rows.iter().enumerate().for_each(|(i, at)| {
+                let Some(item) = item::get(items, at) else {
+                    return;
+                };
                 mv(i as i32, 0);
-                let (cp, s) = match item.as_ref().chars().next() {
-                    Some('0') => (COLOR_PAIR(config::CHECKBOX_TODO_PAIR), "[ ]"),
-                    Some('1') => (COLOR_PAIR(config::CHECKBOX_DONE_PAIR), "[X]"),
-                    _ => (COLOR_PAIR(config::OTHER_PAIR), "[?]"),
+                // subtasks are indented below their parent, parents are marked
+                // by whether their subtasks are shown
+                let marker = match (item.children.is_empty(), item.collapsed) {
+                    (true, _) => "",
+                    (_, true) => "+ ",
+                    _ => "- ",
                 };
-                match i == on_item {
-                    true => {
-                        addstr(&format!("{s}\t "));
+                let prefix = format!("{}{marker}", "  ".repeat(at.len() - 1));
+                display_item(item, &prefix, i == on_item, now);
+            }),
+        }
+
+        if folded > 0 {
+            mv(rows.len() as i32, 0);
+            attron(COLOR_PAIR(config::CHECKBOX_DONE_PAIR));
+            addstr("[+]");
+            attroff(COLOR_PAIR(config::CHECKBOX_DONE_PAIR));
+            addstr(&format!("\t {folded} completed"));
+        }
+
+        if let Some(item) = rows.get(on_item).and_then(|at| item::get(items, at)) {
+            display_notes(&item.notes);
+        }
+
+        Ok(())
+    }
 
+    fn display_archive(&self, on: usize) -> Result<()> {
+        let ctx = self.lock_unwrap();
+        match ctx.archive.is_empty() {
+            true => {
+                addstr("no archived topics atm");
+            }
+            _ => ctx.archive.iter().enumerate().for_each(|(i, archived)| {
+                mv(i as i32, 0);
+                attron(COLOR_PAIR(config::OTHER_PAIR));
+                addstr(&format!("[{}]", archived.archived.format("%Y-%m-%d")));
+                attroff(COLOR_PAIR(config::OTHER_PAIR));
+                addstr("\t ");
+                match i == on {
+                    true => {
                         attron(COLOR_PAIR(config::HIGHLIGHT_PAIR));
-                        addstr(&item.as_ref()[1..]);
+                        addstr(archived.topic.as_ref());
                         attroff(COLOR_PAIR(config::HIGHLIGHT_PAIR));
                     }
                     _ => {
-                        attron(cp);
-                        addstr(s);
-                        attroff(cp);
-
-                        addstr(&format!("\t {}", &item.as_ref()[1..]));
+                        addstr(archived.topic.as_ref());
                     }
                 }
+                addstr(&format!(" ({})", archived.items.len()));
+            }),
+        }
+
+        Ok(())
+    }
+
+    fn display_tagged(&self, tag: &str, on: usize) -> Result<()> {
+        let ctx = self.lock_unwrap();
+        let rows = ctx.tagged(tag);
+        let now = Utc::now();
+        match rows.is_empty() {
+            true => {
+                addstr(&format!("no items tagged #{tag} atm"));
+            }
+            _ => rows.iter().enumerate().for_each(|(i, (on_topic, at))| {
+                let topic = &ctx.idxs[*on_topic];
+                let Some(item) = ctx.data.get(topic).and_then(|items| item::get(items, at)) else {
+                    return;
+                };
+                mv(i as i32, 0);
+                display_item(item, &format!("{topic}: "), i == on, now);
+                if i == on {
+                    display_notes(&item.notes);
+                }
             }),
         }
 
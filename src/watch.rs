@@ -0,0 +1,106 @@
+//! Changes to the store on disk, made by a script or another go22dos.
+use std::ffi::OsString;
+use std::io;
+use std::path::Path;
+
+use inotify::{Inotify, WatchMask};
+
+pub struct Watch {
+    inotify: Inotify,
+    names: Vec<OsString>,
+    buffer: [u8; 4096],
+}
+
+impl Watch {
+    /// Watches the file at `path` for being written and closed, or replaced.
+    /// Its directory is watched rather than the file itself, so that a file
+    /// replaced by another one is followed.
+    pub fn new(path: &str) -> io::Result<Self> {
+        Self::on(path, WatchMask::CLOSE_WRITE | WatchMask::MOVED_TO, &[""])
+    }
+
+    /// Watches a file which is written in place while it is kept open, as
+    /// SQLite does, along with the `-wal` and `-journal` files next to it.
+    pub fn in_place(path: &str) -> io::Result<Self> {
+        let mask = WatchMask::CLOSE_WRITE | WatchMask::MOVED_TO | WatchMask::MODIFY;
+        Self::on(path, mask, &["", "-wal", "-journal"])
+    }
+
+    /// Watches for `mask` on the file at `path` and the ones named like it
+    /// with any of `suffixes`.
+    fn on(path: &str, mask: WatchMask, suffixes: &[&str]) -> io::Result<Self> {
+        let path = Path::new(path);
+        let dir = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let name = path.file_name().ok_or(io::ErrorKind::InvalidInput)?;
+        let names = suffixes
+            .iter()
+            .map(|suffix| {
+                let mut name = name.to_owned();
+                name.push(suffix);
+                name
+            })
+            .collect();
+        let inotify = Inotify::init()?;
+        inotify.watches().add(dir, mask)?;
+        Ok(Self {
+            inotify,
+            names,
+            buffer: [0; 4096],
+        })
+    }
+
+    /// Whether the file has been written since last asked, without waiting
+    /// for it.
+    pub fn changed(&mut self) -> bool {
+        let mut changed = false;
+        while let Ok(events) = self.inotify.read_events(&mut self.buffer) {
+            let mut any = false;
+            events.for_each(|event| {
+                any = true;
+                changed |= event
+                    .name
+                    .is_some_and(|name| self.names.iter().any(|n| n == name));
+            });
+            if !any {
+                break;
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn changes() {
+        let dir = std::env::temp_dir().join(format!("go22dos-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("_.go22dos");
+        std::fs::write(&path, "{}").unwrap();
+
+        let mut watch = Watch::new(&path.to_string_lossy()).unwrap();
+        assert!(!watch.changed());
+        std::fs::write(dir.join("other"), "{}").unwrap();
+        assert!(!watch.changed());
+        std::fs::write(&path, "{\"data\":{}}").unwrap();
+        assert!(watch.changed());
+        assert!(!watch.changed());
+        // replaced, as editors and compactions do
+        std::fs::write(dir.join("tmp"), "{}").unwrap();
+        std::fs::rename(dir.join("tmp"), &path).unwrap();
+        assert!(watch.changed());
+
+        // written to while kept open
+        let mut in_place = Watch::in_place(&path.to_string_lossy()).unwrap();
+        let mut wal = std::fs::File::create(dir.join("_.go22dos-wal")).unwrap();
+        std::io::Write::write_all(&mut wal, b"page").unwrap();
+        assert!(in_place.changed());
+        assert!(!watch.changed());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}